@@ -0,0 +1,181 @@
+use crate::model::Screen;
+use crate::update::{Message, Toggle};
+use std::error::Error;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: inori [OPTIONS] [COMMAND]
+
+Without a command, inori starts the interface. With one, it runs the
+command against mpd and exits.
+
+Commands:
+  play                start playback, or resume it when paused
+  toggle              toggle between play and pause
+  next                play the next song in the queue
+  prev                play the previous song in the queue
+  clear               clear the queue
+  update              update the mpd database and wait until it is done
+  repeat|random|single|consume
+                      toggle the playback option
+  volume <N>          set the volume to N, or adjust it by +N or -N
+  add <ARTIST>        add all songs by the album artist ARTIST
+  status              print the current song and status, formatted with
+                      --format if given
+
+Options:
+  --host <HOST>       mpd host, socket path or [password@]host
+  --port <PORT>       mpd port (default: 6600)
+  --password <PASS>   mpd password
+  --config <PATH>     read the config from PATH
+  --screen <NAME>     start on the library, queue, playlists, files or
+                      track_search screen
+  --format <FORMAT>   format for status, see CONFIGURATION.md
+  -V, --version       print the version and exit
+  -h, --help          print this help and exit
+
+The MPD_HOST and MPD_PORT environment variables are used when neither the
+flags nor the config file set an address. MPD_HOST may take the form
+password@host.";
+
+#[derive(Default)]
+pub struct Args {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub password: Option<String>,
+    pub config: Option<PathBuf>,
+    pub screen: Option<Screen>,
+}
+
+/// A one-shot command run without the interface.
+pub enum Remote {
+    Msg(Message),
+    Volume(String),
+    Add(String),
+    Status(Option<String>),
+}
+
+pub enum Command {
+    Run(Args),
+    Remote(Args, Remote),
+    Help,
+    Version,
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    UnknownArgument(String),
+    MissingValue(String),
+    InvalidValue(String, String),
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::UnknownArgument(a) => write!(f, "unknown argument {}", a),
+            CliError::MissingValue(a) => write!(f, "{} needs a value", a),
+            CliError::InvalidValue(a, v) => {
+                write!(f, "invalid value {} for {}", v, a)
+            }
+        }
+    }
+}
+
+impl Error for CliError {}
+
+/// Parses the arguments after the program name. Options take their value
+/// either as the next argument or after an `=`, and may appear before or
+/// after the command.
+pub fn parse_args(
+    mut args: impl Iterator<Item = String>,
+) -> Result<Command, CliError> {
+    let mut parsed = Args::default();
+    let mut positional = Vec::new();
+    let mut format = None;
+    while let Some(arg) = args.next() {
+        // negative numbers are volume adjustments, not flags
+        if !arg.starts_with('-') || arg.parse::<i64>().is_ok() {
+            positional.push(arg);
+            continue;
+        }
+        if arg == "--" {
+            positional.extend(args.by_ref());
+            break;
+        }
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg, None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| CliError::MissingValue(flag.clone()))
+        };
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--host" => parsed.host = Some(value()?),
+            "--port" => {
+                let v = value()?;
+                parsed.port =
+                    Some(v.parse().map_err(|_| {
+                        CliError::InvalidValue(flag.clone(), v)
+                    })?);
+            }
+            "--password" => parsed.password = Some(value()?),
+            "--format" => format = Some(value()?),
+            "--config" => parsed.config = Some(PathBuf::from(value()?)),
+            "--screen" => {
+                let v = value()?;
+                parsed.screen =
+                    Some(v.parse().map_err(|_| {
+                        CliError::InvalidValue(flag.clone(), v)
+                    })?);
+            }
+            _ => return Err(CliError::UnknownArgument(flag)),
+        }
+    }
+    let mut positional = positional.into_iter();
+    let Some(command) = positional.next() else {
+        return match format {
+            Some(_) => Err(CliError::UnknownArgument("--format".into())),
+            None => Ok(Command::Run(parsed)),
+        };
+    };
+    let operand = positional.next();
+    if let Some(extra) = positional.next() {
+        return Err(CliError::UnknownArgument(extra));
+    }
+    let remote = match (command.as_str(), operand) {
+        ("status", None) => {
+            return Ok(Command::Remote(parsed, Remote::Status(format)))
+        }
+        ("volume", Some(v)) => Remote::Volume(v),
+        ("add", Some(artist)) => Remote::Add(artist),
+        ("volume" | "add", None) => {
+            return Err(CliError::MissingValue(command))
+        }
+        (_, Some(extra)) => return Err(CliError::UnknownArgument(extra)),
+        (name, None) => Remote::Msg(match name {
+            "play" => Message::Play,
+            "toggle" => Message::PlayPause,
+            "next" => Message::NextSong,
+            "prev" => Message::PreviousSong,
+            "clear" => Message::Clear,
+            "update" => Message::UpdateDB,
+            "repeat" => Message::Set(Toggle::Repeat),
+            "random" => Message::Set(Toggle::Random),
+            "single" => Message::Set(Toggle::Single),
+            "consume" => Message::Set(Toggle::Consume),
+            _ => return Err(CliError::UnknownArgument(command)),
+        }),
+    };
+    if format.is_some() {
+        return Err(CliError::UnknownArgument("--format".into()));
+    }
+    Ok(Command::Remote(parsed, remote))
+}
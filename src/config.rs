@@ -1,52 +1,139 @@
+use crate::cli::Args;
 use crate::event_handler::Result;
 use crate::model::*;
 use crate::view::Theme;
 use platform_dirs::AppDirs;
 use ratatui::style::Style;
+use std::env;
 use std::error::Error;
 use std::fmt::Display;
 use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
 use toml::Table;
 use toml::Value;
 pub mod keybind;
-use keybind::{get_message, KeybindMap};
+use keybind::{get_context, get_message, KeyContext, Keybindings, CONTEXTS};
+
+/// Where and how to connect to mpd.
+#[derive(Clone, Default)]
+pub struct MpdTarget {
+    pub address: Option<String>,
+    pub password: Option<String>,
+}
+
+/// A named hierarchy of tags for the library. The artist panel lists the
+/// values of the first tag, and the track panel groups songs by the rest.
+#[derive(Clone)]
+pub struct LibraryView {
+    pub name: String,
+    pub tags: Vec<String>,
+}
+
+impl LibraryView {
+    /// The album artist, album and track tree.
+    pub fn album_artists() -> Self {
+        LibraryView {
+            name: "artists".into(),
+            tags: vec!["albumartist".into(), "album".into()],
+        }
+    }
+}
+
+/// How the track panel of the library orders albums.
+#[derive(Clone, Copy, PartialEq)]
+pub enum AlbumOrder {
+    /// By original release date, or release date when there is none.
+    OriginalDate,
+    Date,
+    Name,
+}
+
+impl AlbumOrder {
+    /// The tags to date an album by, in order of preference.
+    pub fn date_tags(self) -> &'static [&'static str] {
+        match self {
+            AlbumOrder::OriginalDate | AlbumOrder::Name => {
+                &["originaldate", "date"]
+            }
+            AlbumOrder::Date => &["date"],
+        }
+    }
+}
+
+impl FromStr for AlbumOrder {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "originaldate" => Ok(AlbumOrder::OriginalDate),
+            "date" => Ok(AlbumOrder::Date),
+            "name" => Ok(AlbumOrder::Name),
+            _ => Err(format!("unknown album order: {}", s)),
+        }
+    }
+}
 
 pub struct Config {
-    pub keybindings: KeybindMap,
+    pub keybindings: Keybindings,
     pub theme: Theme,
     pub seek_seconds: i64,
+    pub volume_step: i64,
+    pub keybinding_timeout: Option<Duration>,
     pub mpd_address: Option<String>,
+    pub mpd_password: Option<String>,
     pub screens: Vec<Screen>,
+    pub library_views: Vec<LibraryView>,
+    pub album_order: AlbumOrder,
     pub nucleo_prefer_prefix: bool,
 }
 
 impl Config {
     pub fn default() -> Self {
         Config {
-            keybindings: KeybindMap::default(),
+            keybindings: Keybindings::default(),
             theme: Theme::new(),
             seek_seconds: 5,
+            volume_step: 5,
+            keybinding_timeout: None,
             mpd_address: None,
+            mpd_password: None,
             screens: vec![Screen::Library, Screen::Queue],
+            library_views: vec![LibraryView::album_artists()],
+            album_order: AlbumOrder::OriginalDate,
             nucleo_prefer_prefix: false,
         }
     }
 
-    pub fn try_read_config(mut self) -> Result<Self> {
-        let app_dirs = AppDirs::new(Some("inori"), true);
-        let config_file_path =
-            app_dirs.map(|d| d.config_dir.join("config.toml"));
+    /// Reads the config file at `path`, or at the default location if no
+    /// path is given. Only a missing default config file is not an error.
+    pub fn try_read_config(mut self, path: Option<&Path>) -> Result<Self> {
+        let contents = match path {
+            Some(path) => Some(fs::read_to_string(path).map_err(|e| {
+                format!("couldn't read {}: {}", path.display(), e)
+            })?),
+            None => AppDirs::new(Some("inori"), true)
+                .map(|d| d.config_dir.join("config.toml"))
+                .and_then(|p| fs::read_to_string(p).ok()),
+        };
 
-        if let Some(Ok(contents)) = config_file_path.map(fs::read_to_string) {
+        if let Some(contents) = contents {
             let toml = contents.parse::<Table>()?; //failed to parse toml
             for (key, value) in toml {
                 match (key.as_str(), value) {
                     ("keybindings", Value::Table(t)) => {
-                        self.read_keybinds(t)?
+                        self.read_keybinds(t, "")?
                     }
                     ("seek_seconds", Value::Integer(k)) if k > 0 => {
                         self.seek_seconds = k
                     }
+                    ("volume_step", Value::Integer(k)) if k > 0 => {
+                        self.volume_step = k.min(100)
+                    }
+                    ("keybinding_timeout", Value::Integer(k)) if k > 0 => {
+                        self.keybinding_timeout =
+                            Some(Duration::from_millis(k as u64))
+                    }
                     ("theme", Value::Table(t)) => {
                         self.theme = self.theme.apply_theme(t)?
                     }
@@ -59,6 +146,9 @@ impl Config {
                     ("mpd_address", Value::String(addr)) => {
                         self.mpd_address = Some(addr);
                     }
+                    ("mpd_password", Value::String(password)) => {
+                        self.mpd_password = Some(password);
+                    }
                     ("screens", Value::Array(screens)) => {
                         self.screens = screens
                             .iter()
@@ -74,6 +164,12 @@ impl Config {
                             })
                             .collect::<Result<Vec<Screen>>>()?;
                     }
+                    ("library_views", Value::Table(t)) => {
+                        self.library_views = read_library_views(t)?
+                    }
+                    ("album_order", Value::String(s)) => {
+                        self.album_order = s.parse()?
+                    }
                     ("nucleo_prefer_prefix", Value::Boolean(t)) => {
                         self.nucleo_prefer_prefix = t
                     }
@@ -84,18 +180,67 @@ impl Config {
         Ok(self)
     }
 
-    pub fn read_keybinds(&mut self, t: Table) -> Result<()> {
+    /// Applies command line flags, which take precedence over the config
+    /// file.
+    pub fn apply_args(&mut self, args: &Args) {
+        let (password, host) = match (&args.host, args.port) {
+            (Some(host), _) => split_password(host),
+            // a port alone keeps the host and password from the environment
+            (None, Some(_)) => {
+                let (password, host) = env::var("MPD_HOST")
+                    .map(|h| split_password(&h))
+                    .unwrap_or_default();
+                (
+                    password.filter(|_| self.mpd_password.is_none()),
+                    host.filter(|h| !h.is_empty()).or(Some("localhost".into())),
+                )
+            }
+            (None, None) => (None, None),
+        };
+        if let Some(host) = host {
+            self.mpd_address =
+                Some(if host.starts_with('/') || host.starts_with('@') {
+                    host
+                } else {
+                    format!("{}:{}", host, args.port.unwrap_or(6600))
+                });
+        }
+        if let Some(password) = args.password.clone().or(password) {
+            self.mpd_password = Some(password);
+        }
+    }
+
+    pub fn mpd_target(&self) -> MpdTarget {
+        MpdTarget {
+            address: self.mpd_address.clone(),
+            password: self.mpd_password.clone(),
+        }
+    }
+
+    /// Reads the bindings in a `[keybindings]` table. `path` is the dotted
+    /// name of the table below `keybindings`, and is empty for the global
+    /// bindings at the top.
+    pub fn read_keybinds(&mut self, t: Table, path: &str) -> Result<()> {
+        let context = match path {
+            "" => Some(KeyContext::Global),
+            path => get_context(path),
+        };
         for (key, value) in t {
-            match (get_message(&key), value) {
-                (Some(m), Value::String(s)) => {
+            match (get_message(&key), context, value) {
+                (Some(_), None, _) => {
+                    return Err(Box::new(ConfigError::UnknownContext(
+                        path.to_string(),
+                    )))
+                }
+                (Some(m), Some(c), Value::String(s)) => {
                     let keybinds = keybind::parse_keybind(s).unwrap();
-                    self.keybindings.insert(m.clone(), &keybinds);
+                    self.keybindings.insert(c, m.clone(), &keybinds);
                 }
-                (Some(m), Value::Array(a)) => {
+                (Some(m), Some(c), Value::Array(a)) => {
                     for v in a {
                         if let Value::String(s) = v {
                             let keybinds = keybind::parse_keybind(s).unwrap();
-                            self.keybindings.insert(m.clone(), &keybinds);
+                            self.keybindings.insert(c, m.clone(), &keybinds);
                         } else {
                             return Err(Box::new(
                                 ConfigError::WrongKeyValueType(key, v),
@@ -103,12 +248,28 @@ impl Config {
                         }
                     }
                 }
-                (Some(_m), other) => {
+                (Some(_m), _, other) => {
                     return Err(Box::new(ConfigError::WrongKeyValueType(
                         key, other,
                     )))
                 }
-                (None, _) => {
+                (None, _, Value::Table(t)) => {
+                    // contexts like library.artist nest as tables in toml
+                    let name = match path {
+                        "" => key,
+                        path => format!("{}.{}", path, key),
+                    };
+                    let prefix = format!("{}.", name);
+                    if !CONTEXTS.iter().any(|(context, _)| {
+                        *context == name || context.starts_with(&prefix)
+                    }) {
+                        return Err(Box::new(ConfigError::UnknownContext(
+                            name,
+                        )));
+                    }
+                    self.read_keybinds(t, &name)?
+                }
+                (None, _, _) => {
                     return Err(Box::new(ConfigError::MissingMessage(key)))
                 }
             }
@@ -117,6 +278,42 @@ impl Config {
     }
 }
 
+/// Reads the `[library_views]` table, in which every key names a view and
+/// holds its tags, in order.
+fn read_library_views(t: Table) -> Result<Vec<LibraryView>> {
+    let mut views = Vec::new();
+    for (name, value) in t {
+        let tags = match value {
+            Value::Array(a) => a
+                .iter()
+                .map(|v| v.as_str().map(|s| s.to_lowercase()))
+                .collect::<Option<Vec<String>>>(),
+            _ => None,
+        };
+        match tags {
+            Some(tags) if tags.len() >= 2 => {
+                views.push(LibraryView { name, tags })
+            }
+            _ => return Err(Box::new(ConfigError::InvalidLibraryView(name))),
+        }
+    }
+    if views.is_empty() {
+        views.push(LibraryView::album_artists());
+    }
+    Ok(views)
+}
+
+/// Splits the `password@host` form of `MPD_HOST` and `--host`. A leading
+/// `@` marks an abstract socket rather than an empty password.
+pub fn split_password(host: &str) -> (Option<String>, Option<String>) {
+    match host.split_once('@') {
+        Some((password, host)) if !password.is_empty() => {
+            (Some(password.into()), Some(host.into()))
+        }
+        _ => (None, Some(host.into())),
+    }
+}
+
 //does not log or throw. it simply ignores 'bad' mods
 fn join_modifier_array(modifiers: &[Value]) -> String {
     let modifier_strings: Vec<String> = modifiers
@@ -147,6 +344,8 @@ pub fn deserialize_style(mut t: Table) -> Result<Style> {
 #[derive(Debug)]
 pub enum ConfigError {
     MissingMessage(String),
+    UnknownContext(String),
+    InvalidLibraryView(String),
     //UnknownModifier(String),
     UnknownThemeOption(String),
     WrongKeyValueType(String, Value),
@@ -158,6 +357,12 @@ impl Display for ConfigError {
             ConfigError::MissingMessage(s) => {
                 write!(f, "message {} does not exist", s)
             }
+            ConfigError::UnknownContext(s) => {
+                write!(f, "keybinding context {} does not exist", s)
+            }
+            ConfigError::InvalidLibraryView(s) => {
+                write!(f, "library view {} needs at least two tags", s)
+            }
             //ConfigError::UnknownModifier(s) => write!(f, "Error while parsing theme modifier array: unknown modifier: {}", s),
             ConfigError::UnknownThemeOption(s) => {
                 write!(f, "theme option {} not found", s)
@@ -170,3 +375,23 @@ impl Display for ConfigError {
 }
 
 impl Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn library_views_keep_file_order() {
+        let t = "zeta = [\"genre\", \"album\"]\n\
+                 alpha = [\"composer\", \"work\"]\n\
+                 mid = [\"albumartist\", \"album\"]"
+            .parse::<Table>()
+            .unwrap();
+        let names: Vec<String> = read_library_views(t)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.name)
+            .collect();
+        assert_eq!(names, ["zeta", "alpha", "mid"]);
+    }
+}
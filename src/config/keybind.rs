@@ -15,41 +15,77 @@ use KeybindTarget::*;
 pub struct KeybindMap(pub HashMap<KeyEvent, KeybindTarget>);
 const EMPTY: KeyModifiers = KeyModifiers::empty();
 
+/// Every command name, as used in the config file and at the command line.
+pub const MESSAGES: &[(&str, Message)] = &[
+    ("up", Message::Direction(Dirs::Vert(Vertical::Up))),
+    ("down", Message::Direction(Dirs::Vert(Vertical::Down))),
+    ("screenful_up", Message::ScrollScreenful(Vertical::Up)),
+    ("screenful_down", Message::ScrollScreenful(Vertical::Down)),
+    ("top", Message::Direction(Dirs::Vert(Vertical::Top))),
+    ("bottom", Message::Direction(Dirs::Vert(Vertical::Bottom))),
+    ("left", Message::Direction(Dirs::Horiz(Horizontal::Left))),
+    ("right", Message::Direction(Dirs::Horiz(Horizontal::Right))),
+    ("toggle_playpause", Message::PlayPause),
+    ("play", Message::Play),
+    ("update_db", Message::UpdateDB),
+    ("select", Message::Select),
+    ("select_and_next", Message::SelectAndNext),
+    ("quit", Message::SwitchState(State::Done)),
+    ("screen_1", Message::SwitchScreen(1)),
+    ("screen_2", Message::SwitchScreen(2)),
+    ("screen_3", Message::SwitchScreen(3)),
+    ("screen_4", Message::SwitchScreen(4)),
+    ("toggle_screen", Message::ToggleScreen),
+    ("toggle_screen_lq", Message::ToggleScreen),
+    ("toggle_panel", Message::TogglePanel),
+    ("fold", Message::Fold),
+    ("clear_queue", Message::Clear),
+    ("local_search", Message::LocalSearch(SearchMsg::Start)),
+    ("global_search", Message::GlobalSearch(SearchMsg::Start)),
+    ("escape", Message::Escape),
+    ("history_prev", Message::History(Vertical::Up)),
+    ("history_next", Message::History(Vertical::Down)),
+    ("delete", Message::Delete),
+    ("toggle_repeat", Message::Set(Toggle::Repeat)),
+    ("toggle_single", Message::Set(Toggle::Single)),
+    ("toggle_consume", Message::Set(Toggle::Consume)),
+    ("toggle_random", Message::Set(Toggle::Random)),
+    ("next_song", Message::NextSong),
+    ("previous_song", Message::PreviousSong),
+    ("seek", Message::Seek(SeekDirection::Forward)),
+    ("seek_backwards", Message::Seek(SeekDirection::Backward)),
+    ("rename", Message::Rename),
+    ("replace", Message::Replace),
+    ("save_queue", Message::SaveQueue),
+    ("toggle_mark", Message::ToggleMark),
+    ("visual", Message::Visual),
+    ("move_to", Message::MoveTo),
+    ("insert_next", Message::InsertNext),
+    ("play_now", Message::PlayNow),
+    ("move_up", Message::MoveUp),
+    ("move_down", Message::MoveDown),
+    ("next_library_view", Message::NextLibraryView),
+    ("volume_up", Message::VolumeUp),
+    ("volume_down", Message::VolumeDown),
+    ("set_volume", Message::SetVolume),
+    ("outputs", Message::Outputs),
+    ("command_line", Message::CommandLine),
+    ("help", Message::Help),
+];
+
+/// The first name of a message, for showing it to the user.
+pub fn message_name(m: &Message) -> Option<&'static str> {
+    MESSAGES
+        .iter()
+        .find(|(_, msg)| msg == m)
+        .map(|(name, _)| *name)
+}
+
 pub fn get_message(s: &str) -> Option<Message> {
-    match s {
-        "up" => Some(Message::Direction(Dirs::Vert(Vertical::Up))),
-        "down" => Some(Message::Direction(Dirs::Vert(Vertical::Down))),
-        "screenful_up" => Some(Message::ScrollScreenful(Vertical::Up)),
-        "screenful_down" => Some(Message::ScrollScreenful(Vertical::Down)),
-        "top" => Some(Message::Direction(Dirs::Vert(Vertical::Top))),
-        "bottom" => Some(Message::Direction(Dirs::Vert(Vertical::Bottom))),
-        "left" => Some(Message::Direction(Dirs::Horiz(Horizontal::Left))),
-        "right" => Some(Message::Direction(Dirs::Horiz(Horizontal::Right))),
-        "toggle_playpause" => Some(Message::PlayPause),
-        "update_db" => Some(Message::UpdateDB),
-        "select" => Some(Message::Select),
-        "select_and_next" => Some(Message::SelectAndNext),
-        "quit" => Some(Message::SwitchState(State::Done)),
-        "screen_1" => Some(Message::SwitchScreen(1)),
-        "screen_2" => Some(Message::SwitchScreen(2)),
-        "toggle_screen" | "toggle_screen_lq" => Some(Message::ToggleScreen),
-        "toggle_panel" => Some(Message::TogglePanel),
-        "fold" => Some(Message::Fold),
-        "clear_queue" => Some(Message::Clear),
-        "local_search" => Some(Message::LocalSearch(SearchMsg::Start)),
-        "global_search" => Some(Message::GlobalSearch(SearchMsg::Start)),
-        "escape" => Some(Message::Escape),
-        "delete" => Some(Message::Delete),
-        "toggle_repeat" => Some(Message::Set(Toggle::Repeat)),
-        "toggle_single" => Some(Message::Set(Toggle::Single)),
-        "toggle_consume" => Some(Message::Set(Toggle::Consume)),
-        "toggle_random" => Some(Message::Set(Toggle::Random)),
-        "next_song" => Some(Message::NextSong),
-        "previous_song" => Some(Message::PreviousSong),
-        "seek" => Some(Message::Seek(SeekDirection::Forward)),
-        "seek_backwards" => Some(Message::Seek(SeekDirection::Backward)),
-        _ => None,
-    }
+    MESSAGES
+        .iter()
+        .find(|(name, _)| *name == s)
+        .map(|(_, m)| m.clone())
 }
 
 impl KeybindMap {
@@ -103,6 +139,14 @@ impl KeybindMap {
             KeyEvent::new(KeyCode::Char('2'), EMPTY),
             Msg(SwitchScreen(2)),
         );
+        keybindings.insert(
+            KeyEvent::new(KeyCode::Char('3'), EMPTY),
+            Msg(SwitchScreen(3)),
+        );
+        keybindings.insert(
+            KeyEvent::new(KeyCode::Char('4'), EMPTY),
+            Msg(SwitchScreen(4)),
+        );
         keybindings.insert(
             KeyEvent::new(KeyCode::Char('q'), EMPTY),
             Msg(SwitchState(super::State::Done)),
@@ -137,6 +181,31 @@ impl KeybindMap {
         );
         keybindings
             .insert(KeyEvent::new(KeyCode::Char('u'), EMPTY), Msg(UpdateDB));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('R'), EMPTY), Msg(Rename));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('o'), EMPTY), Msg(Replace));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('S'), EMPTY), Msg(SaveQueue));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('m'), EMPTY), Msg(ToggleMark));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('V'), EMPTY), Msg(Visual));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('M'), EMPTY), Msg(MoveTo));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('i'), EMPTY), Msg(InsertNext));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('P'), EMPTY), Msg(PlayNow));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('+'), EMPTY), Msg(VolumeUp));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('_'), EMPTY), Msg(VolumeDown));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char('O'), EMPTY), Msg(Outputs));
+        keybindings
+            .insert(KeyEvent::new(KeyCode::Char(':'), EMPTY), Msg(CommandLine));
+        keybindings.insert(KeyEvent::new(KeyCode::Char('?'), EMPTY), Msg(Help));
 
         keybindings.insert(
             KeyEvent::new(KeyCode::PageDown, EMPTY),
@@ -258,6 +327,22 @@ impl KeybindMap {
             self.insert(msg, bind)
         }
     }
+    /// Every key sequence in the map with the message it is bound to.
+    pub fn bindings(&self) -> Vec<(Vec<KeyEvent>, &Message)> {
+        let mut out = Vec::new();
+        for (key, target) in &self.0 {
+            match target {
+                KeybindTarget::Msg(m) => out.push((vec![*key], m)),
+                KeybindTarget::Map(map) => {
+                    for (mut keys, m) in map.bindings() {
+                        keys.insert(0, *key);
+                        out.push((keys, m));
+                    }
+                }
+            }
+        }
+        out
+    }
     pub fn lookup(&self, bind: &[KeyEvent]) -> Option<&KeybindTarget> {
         if bind.is_empty() {
             return None;
@@ -273,6 +358,162 @@ impl KeybindMap {
     }
 }
 
+/// Where a keybinding applies.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum KeyContext {
+    Global,
+    LibraryArtist,
+    LibraryTracks,
+    Queue,
+    Playlists,
+    Files,
+    TrackSearch,
+    Search,
+}
+
+/// Every context name, as used for the tables in `[keybindings]`.
+pub const CONTEXTS: &[(&str, KeyContext)] = &[
+    ("global", KeyContext::Global),
+    ("library.artist", KeyContext::LibraryArtist),
+    ("library.tracks", KeyContext::LibraryTracks),
+    ("queue", KeyContext::Queue),
+    ("playlists", KeyContext::Playlists),
+    ("files", KeyContext::Files),
+    ("track_search", KeyContext::TrackSearch),
+    ("search", KeyContext::Search),
+];
+
+pub fn get_context(s: &str) -> Option<KeyContext> {
+    CONTEXTS
+        .iter()
+        .find(|(name, _)| *name == s)
+        .map(|(_, c)| *c)
+}
+
+pub fn context_name(c: KeyContext) -> &'static str {
+    CONTEXTS
+        .iter()
+        .find(|(_, context)| *context == c)
+        .map_or("global", |(name, _)| *name)
+}
+
+/// The keybindings of every context. A key bound in the context of the
+/// focused screen shadows the same key in the global context, except in
+/// the search context, where unbound keys are typed into the query.
+pub struct Keybindings(pub HashMap<KeyContext, KeybindMap>);
+
+impl Keybindings {
+    pub fn default() -> Self {
+        let mut search = KeybindMap(HashMap::new());
+        search.insert(
+            Direction(Dirs::Vert(Vertical::Down)),
+            &[KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL)],
+        );
+        search.insert(
+            Direction(Dirs::Vert(Vertical::Up)),
+            &[KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL)],
+        );
+        search.insert(
+            Direction(Dirs::Vert(Vertical::Down)),
+            &[KeyEvent::new(KeyCode::Down, EMPTY)],
+        );
+        search.insert(
+            Direction(Dirs::Vert(Vertical::Up)),
+            &[KeyEvent::new(KeyCode::Up, EMPTY)],
+        );
+        search.insert(
+            History(Vertical::Up),
+            &[KeyEvent::new(KeyCode::Char('p'), KeyModifiers::ALT)],
+        );
+        search.insert(
+            History(Vertical::Down),
+            &[KeyEvent::new(KeyCode::Char('n'), KeyModifiers::ALT)],
+        );
+        search.insert(Select, &[KeyEvent::new(KeyCode::Enter, EMPTY)]);
+        search.insert(ToggleScreen, &[KeyEvent::new(KeyCode::Tab, EMPTY)]);
+        search.insert(Escape, &[KeyEvent::new(KeyCode::Esc, EMPTY)]);
+
+        let mut queue = KeybindMap(HashMap::new());
+        queue.insert(MoveUp, &[KeyEvent::new(KeyCode::Left, EMPTY)]);
+        queue.insert(MoveDown, &[KeyEvent::new(KeyCode::Right, EMPTY)]);
+
+        Self(HashMap::from([
+            (KeyContext::Global, KeybindMap::default()),
+            (KeyContext::Queue, queue),
+            (KeyContext::Search, search),
+        ]))
+    }
+    pub fn with_dvorak_style(mut self) -> Self {
+        let global = self.take(KeyContext::Global).with_dvorak_style();
+        self.0.insert(KeyContext::Global, global);
+        self.insert(
+            KeyContext::Queue,
+            MoveUp,
+            &[KeyEvent::new(KeyCode::Char('d'), EMPTY)],
+        );
+        self.insert(
+            KeyContext::Queue,
+            MoveDown,
+            &[KeyEvent::new(KeyCode::Char('n'), EMPTY)],
+        );
+        self
+    }
+    pub fn with_qwerty_style(mut self) -> Self {
+        let global = self.take(KeyContext::Global).with_qwerty_style();
+        self.0.insert(KeyContext::Global, global);
+        self.insert(
+            KeyContext::Queue,
+            MoveUp,
+            &[KeyEvent::new(KeyCode::Char('h'), EMPTY)],
+        );
+        self.insert(
+            KeyContext::Queue,
+            MoveDown,
+            &[KeyEvent::new(KeyCode::Char('l'), EMPTY)],
+        );
+        self
+    }
+    fn take(&mut self, context: KeyContext) -> KeybindMap {
+        self.0
+            .remove(&context)
+            .unwrap_or_else(|| KeybindMap(HashMap::new()))
+    }
+    pub fn insert(
+        &mut self,
+        context: KeyContext,
+        msg: Message,
+        bind: &[KeyEvent],
+    ) {
+        self.0
+            .entry(context)
+            .or_insert_with(|| KeybindMap(HashMap::new()))
+            .insert(msg, bind);
+    }
+    /// Looks a key sequence up in the context, falling back to the global
+    /// context.
+    pub fn lookup(
+        &self,
+        context: KeyContext,
+        bind: &[KeyEvent],
+    ) -> Option<&KeybindTarget> {
+        let local = self.0.get(&context).and_then(|m| m.lookup(bind));
+        if local.is_some() || context == KeyContext::Search {
+            return local;
+        }
+        self.0.get(&KeyContext::Global).and_then(|m| m.lookup(bind))
+    }
+    /// Every key sequence with its context and the message it is bound to.
+    pub fn bindings(&self) -> Vec<(KeyContext, Vec<KeyEvent>, &Message)> {
+        let mut out = Vec::new();
+        for (context, map) in &self.0 {
+            for (keys, m) in map.bindings() {
+                out.push((*context, keys, m));
+            }
+        }
+        out
+    }
+}
+
 pub fn parse_keybind_single(s: &str) -> Option<KeyCode> {
     if s.len() == 1 {
         s.chars().next().map(KeyCode::Char)
@@ -298,6 +539,52 @@ pub fn parse_keybind_single(s: &str) -> Option<KeyCode> {
     }
 }
 
+/// The inverse of `parse_keybind_single`.
+fn format_keybind_single(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "<space>".into(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Esc => "<escape>".into(),
+        KeyCode::Tab => "<tab>".into(),
+        KeyCode::Backspace => "<backspace>".into(),
+        KeyCode::Delete => "<delete>".into(),
+        KeyCode::Up => "<up>".into(),
+        KeyCode::Down => "<down>".into(),
+        KeyCode::Left => "<left>".into(),
+        KeyCode::Right => "<right>".into(),
+        KeyCode::Enter => "<enter>".into(),
+        KeyCode::Home => "<home>".into(),
+        KeyCode::End => "<end>".into(),
+        KeyCode::PageUp => "<pageup>".into(),
+        KeyCode::PageDown => "<pagedown>".into(),
+        other => other.to_string(),
+    }
+}
+
+/// Formats a key sequence in the syntax `parse_keybind` reads.
+pub fn format_keybind(keys: &[KeyEvent]) -> String {
+    keys.iter()
+        .map(|k| {
+            let prefix = if k
+                .modifiers
+                .contains(KeyModifiers::CONTROL | KeyModifiers::META)
+            {
+                "C-M-"
+            } else if k.modifiers.contains(KeyModifiers::CONTROL) {
+                "C-"
+            } else if k.modifiers.contains(KeyModifiers::ALT) {
+                "M-"
+            } else if k.modifiers.contains(KeyModifiers::SUPER) {
+                "S-"
+            } else {
+                ""
+            };
+            format!("{}{}", prefix, format_keybind_single(k.code))
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
 #[derive(Debug)]
 pub enum KeybindParseError {
     EmptyKeybindParseError,
@@ -1,4 +1,10 @@
+use crate::config::MpdTarget;
+use crate::model::{Model, BACKOFF_MAX, BACKOFF_START, IDLE_SUBSYSTEMS};
+use crate::worker::Response;
+use mpd::idle::Idle;
+use mpd::Subsystem;
 use ratatui::crossterm;
+use std::sync::mpsc::{RecvTimeoutError, Sender};
 use std::time::{Duration, Instant};
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
@@ -6,9 +12,12 @@ pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 pub enum Event {
     Tick,
     Key(crossterm::event::KeyEvent),
+    Mpd(Vec<Subsystem>),
+    Worker(Response),
 }
 
 pub struct EventHandler {
+    tx: Sender<Event>,
     rx: std::sync::mpsc::Receiver<Event>,
 }
 
@@ -18,6 +27,7 @@ impl EventHandler {
         const TICK_INTERVAL: Duration = Duration::from_millis(500);
 
         let (tx, rx) = std::sync::mpsc::channel();
+        let key_tx = tx.clone();
         let mut now = Instant::now();
         let mut last_event = Instant::now();
         std::thread::spawn(move || loop {
@@ -25,7 +35,7 @@ impl EventHandler {
                 match crossterm::event::read().expect("event read failed") {
                     crossterm::event::Event::Key(e) => {
                         last_event = Instant::now();
-                        tx.send(Event::Key(e))
+                        key_tx.send(Event::Key(e))
                     }
                     crossterm::event::Event::Resize(_, _) => Ok(()),
                     _ => unimplemented!(),
@@ -36,14 +46,59 @@ impl EventHandler {
             let time_since_last_event: Duration = Instant::now() - last_event;
             if now.elapsed() >= TICK_INTERVAL && time_since_last_event >= TICK_INTERVAL
             {
-                tx.send(Event::Tick).expect("tick send failed");
+                key_tx.send(Event::Tick).expect("tick send failed");
                 now = Instant::now();
             }
         });
-        EventHandler { rx }
+        EventHandler { tx, rx }
+    }
+
+    // after a reconnect every subsystem counts as changed
+    pub fn watch_mpd(&self, target: MpdTarget) {
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            let mut delay = BACKOFF_START;
+            let mut reconnecting = false;
+            loop {
+                let mut client = match Model::make_connection(&target) {
+                    Ok(c) => c,
+                    Err(_) => {
+                        reconnecting = true;
+                        std::thread::sleep(delay);
+                        delay = (delay * 2).min(BACKOFF_MAX);
+                        continue;
+                    }
+                };
+                delay = BACKOFF_START;
+                if reconnecting
+                    && tx.send(Event::Mpd(IDLE_SUBSYSTEMS.to_vec())).is_err()
+                {
+                    return;
+                }
+                while let Ok(changes) = client.wait(&IDLE_SUBSYSTEMS) {
+                    if tx.send(Event::Mpd(changes)).is_err() {
+                        return;
+                    }
+                }
+                reconnecting = true;
+                std::thread::sleep(delay);
+            }
+        });
+    }
+
+    pub fn sender(&self) -> Sender<Event> {
+        self.tx.clone()
     }
 
     pub fn next(&self) -> Result<Event> {
         Ok(self.rx.recv()?)
     }
+
+    pub fn next_timeout(&self, timeout: Duration) -> Result<Option<Event>> {
+        match self.rx.recv_timeout(timeout) {
+            Ok(event) => Ok(Some(event)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
 }
@@ -13,16 +13,52 @@ use ratatui::{
 };
 use std::io::stdout;
 use std::panic;
-use update::Update;
+mod cli;
 mod config;
 mod event_handler;
 mod model;
+mod remote;
 mod update;
 mod util;
 mod view;
+mod worker;
 use event_handler::{Event, Result};
 
 fn main() -> Result<()> {
+    let (args, remote) = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(cli::Command::Run(args)) => (args, None),
+        Ok(cli::Command::Remote(args, remote)) => (args, Some(remote)),
+        Ok(cli::Command::Help) => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Ok(cli::Command::Version) => {
+            println!("inori {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("inori: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    let mut config = match config::Config::default()
+        .try_read_config(args.config.as_deref())
+    {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("inori: {}", e);
+            std::process::exit(1);
+        }
+    };
+    config.apply_args(&args);
+    if let Some(remote) = remote {
+        if let Err(e) = remote::run(config, remote) {
+            eprintln!("inori: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     stdout().execute(EnterAlternateScreen)?;
     enable_raw_mode()?;
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
@@ -34,20 +70,49 @@ fn main() -> Result<()> {
         hook(panic);
     }));
 
-    let mut model = model::Model::new(terminal.get_frame().area())
-        .expect("Failed to init.");
-    update::update_tick(&mut model)?;
-    update::update_screens(&mut model, Update::empty())?;
+    let event_handler = event_handler::EventHandler::new();
+    let mut model = match model::Model::new(
+        terminal.get_frame().area(),
+        event_handler.sender(),
+        config,
+    ) {
+        Ok(model) => model,
+        Err(e) => {
+            reset_terminal()?;
+            eprintln!("inori: couldn't connect to mpd: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(screen) = args.screen {
+        if screen != model.screen {
+            model.toggle_screen = std::mem::replace(&mut model.screen, screen);
+        }
+    }
+    if let Err(e) = update::rebuild(&mut model) {
+        model.handle_error(e)?;
+    }
     terminal.draw(|f| view::view(&mut model, f))?;
 
-    let event_handler = event_handler::EventHandler::new();
+    event_handler.watch_mpd(model.config.mpd_target());
     loop {
-        match event_handler.next()? {
-            Event::Tick => update::update_tick(&mut model)?,
-            Event::Key(k) => {
-                let update = update::handle_key(&mut model, k)?;
-                update::update_screens(&mut model, update)?;
+        let event = match update::timeout(&model) {
+            Some(timeout) => event_handler.next_timeout(timeout)?,
+            None => Some(event_handler.next()?),
+        };
+        let res = match event {
+            None => update::update_timeout(&mut model),
+            Some(Event::Tick) => update::update_tick(&mut model),
+            Some(Event::Mpd(changes)) => {
+                update::handle_idle(&mut model, &changes)
+            }
+            Some(Event::Worker(response)) => {
+                update::handle_response(&mut model, response)
             }
+            Some(Event::Key(k)) => update::handle_key(&mut model, k)
+                .and_then(|update| update::update_screens(&mut model, update)),
+        };
+        if let Err(e) = res {
+            model.handle_error(e)?;
         }
         model.frame_size = terminal.get_frame().area();
         terminal.draw(|f| view::view(&mut model, f))?;
@@ -56,6 +121,9 @@ fn main() -> Result<()> {
         }
     }
     reset_terminal().expect("Failed to reset terminal.");
+    if let Err(e) = model.history.save() {
+        eprintln!("inori: couldn't save search history: {}", e);
+    }
     Ok(())
 }
 
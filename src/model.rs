@@ -2,45 +2,76 @@ extern crate mpd;
 use mpd::client::StreamTypes;
 use std::error::Error;
 //use mpd::error::Result;
-use mpd::idle::IdleClient;
-use mpd::{Client, Song, Status, Subsystem};
+use mpd::{Client, Output, Song, Status, Subsystem};
 use nucleo_matcher::{Matcher, Utf32String};
 use ratatui::crossterm::event::KeyEvent;
 use ratatui::layout::Rect;
 use ratatui::widgets::*;
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::{Duration, Instant};
 mod impl_album_song;
 mod impl_artiststate;
+mod impl_connection;
+mod impl_files;
+mod impl_help;
+mod impl_history;
 mod impl_library;
+mod impl_notifications;
+mod impl_outputs;
+mod impl_playlists;
+mod impl_prompt;
 mod impl_queue;
 mod impl_searchstate;
+mod impl_track_search;
 pub mod proto;
+pub use impl_connection::is_connection_error;
 mod search_utils;
-use crate::config::Config;
+use crate::config::keybind::KeyContext;
+use crate::config::{Config, LibraryView};
+use crate::event_handler::Event;
 use crate::model::proto::*;
 use crate::update::build_library;
+use crate::worker::{Request, Worker};
 
 type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Screen {
     Library,
     Queue,
+    Playlists,
+    Files,
+    TrackSearch,
+}
+
+impl FromStr for Screen {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "library" | "Library" => Ok(Screen::Library),
+            "queue" | "Queue" => Ok(Screen::Queue),
+            "playlists" | "Playlists" => Ok(Screen::Playlists),
+            "files" | "Files" => Ok(Screen::Files),
+            "track_search" | "TrackSearch" => Ok(Screen::TrackSearch),
+            _ => Err(format!("unknown screen: {}", s)),
+        }
+    }
 }
 
 impl From<&String> for Screen {
     fn from(s: &String) -> Self {
-        match s.as_str() {
-            "library" | "Library" => Screen::Library,
-            "queue" | "Queue" => Screen::Queue,
-            _ => panic!("unknown screen: {}", s),
-        }
+        s.parse().unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum State {
     Searching,
     Running,
+    Prompting,
     Done,
 }
 
@@ -48,6 +79,11 @@ pub enum State {
 pub struct AlbumData {
     pub expanded: bool,
     pub name: String,
+    /// The values of the tags the tracks are grouped by, below the first
+    /// tag of the library view.
+    pub values: Vec<String>,
+    /// The date the album is ordered and labelled by, if it has one.
+    pub date: Option<String>,
     pub tracks: Vec<Song>,
 }
 
@@ -103,49 +139,216 @@ pub struct GlobalSearchState {
     pub results_state: ListState,
 }
 
+#[derive(Clone, PartialEq, Eq)]
+pub enum LibraryMark {
+    Album { artist: String, album: Vec<String> },
+    Song(String),
+}
+
 pub struct LibraryState {
+    /// The index of the library view in the config.
+    pub view: usize,
     pub artist_search: Filter,
     pub global_search: GlobalSearchState,
     pub active: LibActiveSelector,
     pub contents: Vec<ArtistData>,
     pub artist_state: ListState,
+    pub marked: Vec<LibraryMark>,
+    // global search result waiting for its artist's songs
+    pub jump: Option<InfoEntry>,
 }
 
 pub struct QueueSelector {
     pub search: Filter,
     pub contents: Vec<Song>,
     pub state: TableState,
+    pub marked: HashSet<u32>,
+    pub visual_anchor: Option<u32>,
+}
+
+pub struct PlaylistData {
+    pub name: String,
+    pub fetched: bool,
+    pub tracks: Vec<Song>,
+    pub track_state: TableState,
+}
+
+pub enum PlaylistActiveSelector {
+    PlaylistSelector,
+    TrackSelector,
+}
+
+pub struct PlaylistsState {
+    pub search: Filter,
+    pub active: PlaylistActiveSelector,
+    pub fetched: bool,
+    pub contents: Vec<PlaylistData>,
+    pub playlist_state: ListState,
+}
+
+pub enum FileEntry {
+    Directory(String),
+    Song(Song),
+}
+
+pub struct FilesState {
+    pub search: Filter,
+    pub path: Vec<String>,
+    pub parents: Vec<Option<usize>>,
+    pub fetched: bool,
+    pub contents: Vec<FileEntry>,
+    pub state: TableState,
+}
+
+/// Queries submitted to one search, oldest first.
+pub struct SearchHistory {
+    pub entries: Vec<String>,
+    /// The entry recalled into the query, if the query wasn't edited since.
+    pub recalled: Option<usize>,
+    /// Whether entries were added since the history was saved.
+    pub changed: bool,
+}
+
+/// The search histories kept between sessions, in the state directory.
+pub struct SearchHistories {
+    pub path: Option<PathBuf>,
+    pub artist: SearchHistory,
+    pub tracks: SearchHistory,
+    pub queue: SearchHistory,
+    pub global: SearchHistory,
+}
+
+/// Songs from the whole database that match a query sent to mpd's `search`.
+pub struct TrackSearchState {
+    pub query: String,
+    /// Whether the query changed since it was last sent.
+    pub dirty: bool,
+    pub typed_at: Instant,
+    pub contents: Vec<Song>,
+    pub state: TableState,
+}
+
+pub enum PromptKind {
+    RenamePlaylist(String),
+    DeletePlaylist(String),
+    SaveQueue,
+    OverwritePlaylist(String),
+    MoveTo,
+    SetVolume,
+    /// The `:` command line, with the candidates of the last completion.
+    Command {
+        completions: Vec<String>,
+    },
+}
+
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+/// An action in the help overlay, with every key sequence bound to it.
+pub struct HelpEntry {
+    pub name: String,
+    pub keys: Vec<String>,
+}
+
+pub struct HelpPopup {
+    pub search: Filter,
+    pub contents: Vec<HelpEntry>,
+    pub state: TableState,
+}
+
+pub struct OutputsPopup {
+    pub contents: Vec<Output>,
+    pub state: ListState,
+}
+
+/// The subsystems inori listens to with `idle`.
+pub const IDLE_SUBSYSTEMS: [Subsystem; 8] = [
+    Subsystem::Database,
+    Subsystem::Update,
+    Subsystem::Playlist,
+    Subsystem::Queue,
+    Subsystem::Player,
+    Subsystem::Mixer,
+    Subsystem::Output,
+    Subsystem::Options,
+];
+pub const BACKOFF_START: Duration = Duration::from_millis(500);
+pub const BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+pub enum NotificationLevel {
+    Info,
+    Error,
+}
+
+pub struct Notification {
+    pub level: NotificationLevel,
+    pub text: String,
+    pub time: Instant,
+}
+
+/// Sent by the background reconnection thread.
+pub enum Reconnect {
+    Retrying(u32),
+    Connected(Client<StreamTypes>),
+}
+
+pub enum ConnectionState {
+    Connected,
+    Disconnected {
+        attempt: u32,
+        rx: Receiver<Reconnect>,
+    },
 }
 
 pub struct Model {
     pub state: State,
+    pub connection: ConnectionState,
     pub status: Status,
+    pub status_at: Instant,
     pub conn: Client<StreamTypes>,
-    pub idle_conn: IdleClient<StreamTypes>,
+    pub pinged_at: Instant,
     pub screen: Screen,
     pub toggle_screen: Screen,
     pub library: LibraryState,
     pub queue: QueueSelector,
+    pub playlists: PlaylistsState,
+    pub files: FilesState,
+    pub track_search: TrackSearchState,
+    pub history: SearchHistories,
+    pub prompt: Option<Prompt>,
+    pub outputs: Option<OutputsPopup>,
+    pub help: Option<HelpPopup>,
+    pub notifications: VecDeque<Notification>,
+    /// The queue length before songs were added, reported once the status
+    /// is refreshed.
+    pub added_from: Option<u32>,
+    pub worker: Worker,
     pub currentsong: Option<Song>,
     pub matcher: nucleo_matcher::Matcher,
     pub config: Config,
     pub parse_state: Vec<KeyEvent>,
+    pub parse_state_at: Instant,
+    /// The count typed before the pending key sequence, if any.
+    pub parse_count: Option<usize>,
     pub frame_size: Rect,
 }
 
 impl Model {
-    pub fn new(frame_size: Rect) -> Result<Self> {
-        let config = Config::default().try_read_config()?;
-        let mut conn = Self::make_connection(&config);
-        let idle_conn = IdleClient::new(
-            Self::make_connection(&config),
-            &[Subsystem::Database, Subsystem::Player, Subsystem::Options],
-        )?;
+    pub fn new(
+        frame_size: Rect,
+        events: Sender<Event>,
+        config: Config,
+    ) -> Result<Self> {
+        let mut conn = Self::make_connection(&config.mpd_target())?;
         Ok(Model {
             state: State::Running,
+            connection: ConnectionState::Connected,
             status: conn.status()?,
+            status_at: Instant::now(),
             conn,
-            idle_conn,
+            pinged_at: Instant::now(),
             screen: config.screens.first().cloned().unwrap_or(Screen::Library),
             toggle_screen: config
                 .screens
@@ -154,6 +357,16 @@ impl Model {
                 .unwrap_or(Screen::Queue),
             library: LibraryState::new(),
             queue: QueueSelector::new(),
+            playlists: PlaylistsState::new(),
+            files: FilesState::new(),
+            track_search: TrackSearchState::new(),
+            history: SearchHistories::load(),
+            prompt: None,
+            outputs: None,
+            help: None,
+            notifications: VecDeque::new(),
+            added_from: None,
+            worker: Worker::new(config.mpd_target(), events),
             currentsong: None,
             matcher: {
                 let mut default_config = nucleo_matcher::Config::DEFAULT;
@@ -162,34 +375,72 @@ impl Model {
             },
             config,
             parse_state: Vec::new(),
+            parse_state_at: Instant::now(),
+            parse_count: None,
             frame_size,
         })
     }
 
-    pub fn make_connection(conf: &Config) -> Client<StreamTypes> {
-        if let Some(mpd_url) = &conf.mpd_address {
-            Client::<StreamTypes>::connect(mpd_url).unwrap()
-        } else {
-            Client::<StreamTypes>::default()
-        }
-    }
-
     pub fn update_status(&mut self) -> Result<()> {
         self.status = self.conn.status()?;
+        self.status_at = Instant::now();
         Ok(())
     }
+    // moves the progress bar without asking mpd for the status
+    pub fn extrapolate_elapsed(&mut self) {
+        let now = Instant::now();
+        let delta = now - self.status_at;
+        self.status_at = now;
+        if self.status.state != mpd::State::Play {
+            return;
+        }
+        if let Some(elapsed) = self.status.elapsed.as_mut() {
+            *elapsed += delta;
+            if let Some(duration) = self.status.duration {
+                *elapsed = (*elapsed).min(duration);
+            }
+        }
+        if let Some((elapsed, total)) = self.status.time.as_mut() {
+            *elapsed = (*elapsed + delta).min(*total);
+        }
+    }
+    /// The keybinding context of whatever has focus. Popups use the global
+    /// bindings.
+    pub fn key_context(&self) -> KeyContext {
+        if self.help.is_some() || self.outputs.is_some() {
+            return KeyContext::Global;
+        }
+        match (&self.screen, &self.library.active) {
+            (Screen::Library, LibActiveSelector::ArtistSelector) => {
+                KeyContext::LibraryArtist
+            }
+            (Screen::Library, LibActiveSelector::TrackSelector) => {
+                KeyContext::LibraryTracks
+            }
+            (Screen::Queue, _) => KeyContext::Queue,
+            (Screen::Playlists, _) => KeyContext::Playlists,
+            (Screen::Files, _) => KeyContext::Files,
+            (Screen::TrackSearch, _) => KeyContext::TrackSearch,
+        }
+    }
+    pub fn library_view(&self) -> &LibraryView {
+        &self.config.library_views[self.library.view]
+    }
+    /// Asks the worker for the artist panel entries of the library view.
+    pub fn request_library(&mut self) {
+        let tag = self.library_view().tags[0].clone();
+        self.worker.request(Request::Library(tag));
+    }
+    /// How many times to apply the message being handled.
+    pub fn count(&self) -> usize {
+        self.parse_count.unwrap_or(1)
+    }
     pub fn update_currentsong(&mut self) -> Result<()> {
         self.currentsong = self.conn.currentsong()?;
         Ok(())
     }
 
-    pub fn update_global_search_contents(&mut self) -> Result<()> {
-        let mut res = self.conn.list_groups(vec![
-            "title",
-            "album",
-            "albumartistsort",
-            "albumartist",
-        ])?;
+    pub fn set_global_search_contents(&mut self, mut res: Vec<Vec<String>>) {
         self.library.global_search.contents = Some(
             res.iter_mut()
                 .filter_map(|vec| {
@@ -202,7 +453,21 @@ impl Model {
                 })
                 .collect::<Vec<InfoEntry>>(),
         );
-        Ok(())
+    }
+
+    pub fn finish_jump(&mut self, artist: &str) {
+        let selected = self
+            .library
+            .selected_item()
+            .is_some_and(|a| a.name == artist);
+        match self.library.jump.take() {
+            Some(target) if target.artist == artist => {
+                if selected {
+                    self.jump_to(target);
+                }
+            }
+            other => self.library.jump = other,
+        }
     }
 
     pub fn jump_to(&mut self, target: InfoEntry) {
@@ -223,8 +488,9 @@ impl Model {
             return;
         }
         if self.library.selected_item().is_some_and(|i| !i.fetched) {
-            build_library::add_tracks(self)
-                .expect("couldn't add tracks on the fly while searching");
+            build_library::add_tracks(self);
+            self.library.jump = Some(target);
+            return;
         }
         if let Some(artist) = self.library.selected_item_mut() {
             let mut idx: Option<usize> = None;
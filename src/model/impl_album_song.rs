@@ -8,4 +8,9 @@ impl AlbumData {
             .map(|i| i.duration.unwrap_or(Duration::from_secs(0)))
             .sum()
     }
+    /// The year at the start of the album's date.
+    pub fn year(&self) -> Option<&str> {
+        let date = self.date.as_deref()?;
+        Some(date.get(..4).unwrap_or(date))
+    }
 }
@@ -141,9 +141,18 @@ impl<'a> ArtistData {
             }
             self.search.cache.utfstrings_cache = Some(tmp);
         }
+        // albums have no song, in the same order as the cached strings
+        let songs: Vec<Option<&Song>> = self
+            .albums
+            .iter()
+            .flat_map(|album| {
+                std::iter::once(None).chain(album.tracks.iter().map(Some))
+            })
+            .collect();
         self.search.cache.order = compute_orders(
             &self.search.query,
             self.search.cache.utfstrings_cache.as_ref().unwrap(),
+            &songs,
             matcher,
             0,
         );
@@ -201,7 +210,7 @@ impl ArtistData {
         }
     }
     pub fn to_fuzzy_find_str(&self) -> String {
-        if self.sort_names.first().is_some_and(|n| *n == self.name) {
+        if self.sort_names.first().is_none_or(|n| *n == self.name) {
             self.name.clone()
         } else {
             format!("{} [{}]", self.name, self.sort_names.join(", "))
@@ -0,0 +1,141 @@
+use super::*;
+use crate::config::{split_password, MpdTarget};
+use std::env;
+use std::sync::mpsc;
+use std::thread;
+
+// as opposed to mpd refusing a single command
+pub fn is_connection_error(e: &(dyn Error + 'static)) -> bool {
+    match e.downcast_ref::<mpd::error::Error>() {
+        Some(mpd::error::Error::Server(_)) => false,
+        Some(_) => true,
+        None => e.is::<std::io::Error>(),
+    }
+}
+
+// host:port, a socket path or, on linux, an abstract @name socket
+fn connect(addr: &str) -> Result<Client<StreamTypes>> {
+    #[cfg(unix)]
+    if addr.starts_with('/') {
+        return Ok(Client::connect_unix(addr)?);
+    }
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if let Some(name) = addr.strip_prefix('@') {
+        use std::os::linux::net::SocketAddrExt;
+        use std::os::unix::net::{SocketAddr, UnixStream};
+        let stream = UnixStream::connect_addr(
+            &SocketAddr::from_abstract_name(name.as_bytes())?,
+        )?;
+        return Ok(Client::new(StreamTypes::Unix(stream))?);
+    }
+    Ok(Client::connect_tcp(addr)?)
+}
+
+// Client::default panics when no server is found
+fn default_connection() -> Result<(Client<StreamTypes>, Option<String>)> {
+    let (password, host) = match env::var("MPD_HOST") {
+        Ok(var) => split_password(&var),
+        Err(_) => (None, None),
+    };
+    let host = host.filter(|h| !h.is_empty());
+    let port = env::var("MPD_PORT").ok();
+    // an address from the environment is the only one tried
+    if host.is_some() || port.is_some() {
+        let host = host.unwrap_or_else(|| "localhost".into());
+        let addr = if host.starts_with('/') || host.starts_with('@') {
+            host
+        } else {
+            format!("{}:{}", host, port.as_deref().unwrap_or("6600"))
+        };
+        return connect(&addr).map(|conn| (conn, password)).map_err(|e| {
+            format!("unable to connect to mpd at {}: {}", addr, e).into()
+        });
+    }
+    let mut candidates = Vec::new();
+    match env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) => candidates.push(format!("{}/mpd/socket", dir)),
+        Err(_) => candidates.push("/run/mpd/socket".into()),
+    }
+    candidates.push("localhost:6600".into());
+    candidates
+        .iter()
+        .find_map(|addr| connect(addr).ok())
+        .map(|conn| (conn, password))
+        .ok_or_else(|| {
+            format!("unable to find mpd at any of {}", candidates.join(", "))
+                .into()
+        })
+}
+
+impl Model {
+    pub fn make_connection(target: &MpdTarget) -> Result<Client<StreamTypes>> {
+        let (mut conn, env_password) = match &target.address {
+            Some(mpd_url) => (connect(mpd_url)?, None),
+            None => default_connection()?,
+        };
+        if let Some(password) =
+            target.password.as_ref().or(env_password.as_ref())
+        {
+            conn.login(password)?;
+        }
+        Ok(conn)
+    }
+
+    pub fn handle_error(&mut self, e: Box<dyn Error>) -> Result<()> {
+        if let Some(mpd::error::Error::Server(err)) =
+            e.downcast_ref::<mpd::error::Error>()
+        {
+            self.notify_error(format!("{}: {}", err.command, err.detail));
+            Ok(())
+        } else if is_connection_error(e.as_ref()) {
+            self.disconnect();
+            Ok(())
+        } else {
+            Err(e)
+        }
+    }
+
+    pub fn disconnect(&mut self) {
+        if let ConnectionState::Disconnected { .. } = self.connection {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let target = self.config.mpd_target();
+        thread::spawn(move || {
+            let mut delay = BACKOFF_START;
+            for attempt in 1.. {
+                thread::sleep(delay);
+                let msg = match Self::make_connection(&target) {
+                    Ok(conn) => Reconnect::Connected(conn),
+                    Err(_) => Reconnect::Retrying(attempt),
+                };
+                let done = matches!(msg, Reconnect::Connected(..));
+                if tx.send(msg).is_err() || done {
+                    break;
+                }
+                delay = (delay * 2).min(BACKOFF_MAX);
+            }
+        });
+        self.connection = ConnectionState::Disconnected { attempt: 0, rx };
+    }
+
+    // true once the connection has been replaced
+    pub fn poll_reconnect(&mut self) -> bool {
+        let ConnectionState::Disconnected { attempt, rx } =
+            &mut self.connection
+        else {
+            return false;
+        };
+        while let Ok(msg) = rx.try_recv() {
+            match msg {
+                Reconnect::Retrying(n) => *attempt = n,
+                Reconnect::Connected(conn) => {
+                    self.conn = conn;
+                    self.connection = ConnectionState::Connected;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,151 @@
+use super::proto::*;
+use super::*;
+use nucleo_matcher::Matcher;
+use search_utils::{compute_indices, compute_orders};
+
+impl FileEntry {
+    pub fn path(&self) -> &str {
+        match self {
+            FileEntry::Directory(path) => path,
+            FileEntry::Song(song) => &song.file,
+        }
+    }
+    /// The last component of the entry's path.
+    pub fn name(&self) -> &str {
+        let path = self.path();
+        path.rsplit('/').next().unwrap_or(path)
+    }
+}
+
+impl FilesState {
+    pub fn new() -> Self {
+        Self {
+            search: Filter::new(),
+            path: Vec::new(),
+            parents: Vec::new(),
+            fetched: false,
+            contents: Vec::new(),
+            state: TableState::default(),
+        }
+    }
+    pub fn current_dir(&self) -> String {
+        self.path.join("/")
+    }
+    /// Moves into the given directory, which should be a child of the
+    /// current one.
+    pub fn descend(&mut self, dir: &str) {
+        self.parents.push(self.selected());
+        self.path = dir.split('/').map(String::from).collect();
+        self.reset_listing();
+        self.set_selected(None);
+    }
+    pub fn ascend(&mut self) {
+        if self.path.pop().is_some() {
+            self.reset_listing();
+            let parent = self.parents.pop().flatten();
+            self.set_selected(parent);
+        }
+    }
+    fn reset_listing(&mut self) {
+        self.fetched = false;
+        self.contents.clear();
+        self.search.set_off();
+        self.search.cache = FilterCache::new();
+    }
+}
+
+impl Selector for FilesState {
+    fn selector(&self) -> &impl SelectorState {
+        &self.state
+    }
+    fn selector_mut(&mut self) -> &mut impl SelectorState {
+        &mut self.state
+    }
+    fn len(&self) -> usize {
+        self.contents_vec().len()
+    }
+}
+
+impl Searchable<FileEntry> for FilesState {
+    fn filter(&self) -> &Filter {
+        &self.search
+    }
+    fn filter_mut(&mut self) -> &mut Filter {
+        &mut self.search
+    }
+    fn contents(&self) -> Box<dyn Iterator<Item = &FileEntry> + '_> {
+        if self.should_filter() {
+            Box::new(
+                self.filter()
+                    .cache
+                    .order
+                    .iter()
+                    .filter_map(|idx| idx.map(|i| &self.contents[i])),
+            )
+        } else {
+            Box::new(self.contents.iter())
+        }
+    }
+    fn selected_item_mut(&mut self) -> Option<&mut FileEntry> {
+        if self.should_filter() {
+            self.selector()
+                .selected()
+                .and_then(|i| self.search.cache.order.get(i).cloned())
+                .and_then(|i| self.contents.get_mut(i?))
+        } else {
+            self.selector()
+                .selected()
+                .and_then(|i| self.contents.get_mut(i))
+        }
+    }
+    fn update_filter_cache(
+        &mut self,
+        matcher: &mut Matcher,
+        top_k: Option<usize>,
+    ) {
+        if self.filter().cache.query == self.filter().query {
+            return;
+        }
+        if self.filter().cache.utfstrings_cache.is_none() {
+            self.filter_mut().cache.utfstrings_cache = Some(
+                self.contents
+                    .iter()
+                    .map(|i| Utf32String::from(i.name()))
+                    .collect(),
+            );
+        }
+        self.filter_mut().cache.query = self.filter().query.clone();
+        let songs: Vec<Option<&Song>> = self
+            .contents
+            .iter()
+            .map(|entry| match entry {
+                FileEntry::Song(song) => Some(song),
+                FileEntry::Directory(_) => None,
+            })
+            .collect();
+        self.search.cache.order = compute_orders(
+            &self.search.query,
+            self.search.cache.utfstrings_cache.as_ref().unwrap(),
+            &songs,
+            matcher,
+            0,
+        );
+
+        let strings_iterator = self
+            .filter()
+            .cache
+            .order
+            .iter()
+            .take_while(|i| i.is_some())
+            .map(|i| {
+                &self.filter().cache.utfstrings_cache.as_ref().unwrap()
+                    [i.unwrap()]
+            });
+        let strings: Vec<&Utf32String> = match top_k {
+            Some(k) => strings_iterator.take(k).collect(),
+            None => strings_iterator.collect(),
+        };
+        self.filter_mut().cache.indices =
+            compute_indices(&self.filter().query, strings, matcher);
+    }
+}
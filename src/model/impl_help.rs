@@ -0,0 +1,145 @@
+use super::proto::*;
+use super::*;
+use crate::config::keybind::{
+    context_name, format_keybind, KeyContext, Keybindings, MESSAGES,
+};
+use nucleo_matcher::Matcher;
+use search_utils::{compute_indices, compute_orders};
+
+impl HelpEntry {
+    pub fn to_search_string(&self) -> String {
+        format!("{} {}", self.name, self.keys.join(", "))
+    }
+}
+
+impl HelpPopup {
+    /// Lists the bound actions in the order of `MESSAGES`, so aliases of a
+    /// command only show up once. Keys outside the global context are
+    /// followed by the name of their context.
+    pub fn new(keybindings: &Keybindings) -> Self {
+        let bindings = keybindings.bindings();
+        let mut seen = Vec::new();
+        let mut contents = Vec::new();
+        for (name, message) in MESSAGES {
+            if seen.contains(&message) {
+                continue;
+            }
+            seen.push(message);
+            let mut keys: Vec<String> = bindings
+                .iter()
+                .filter(|(_, _, m)| *m == message)
+                .map(|(context, keys, _)| match context {
+                    KeyContext::Global => format_keybind(keys),
+                    c => format!(
+                        "{} ({})",
+                        format_keybind(keys),
+                        context_name(*c)
+                    ),
+                })
+                .collect();
+            if keys.is_empty() {
+                continue;
+            }
+            keys.sort();
+            contents.push(HelpEntry {
+                name: name.to_string(),
+                keys,
+            });
+        }
+        let mut popup = Self {
+            search: Filter::new(),
+            contents,
+            state: TableState::default(),
+        };
+        popup.init();
+        popup
+    }
+}
+
+impl Selector for HelpPopup {
+    fn selector(&self) -> &impl SelectorState {
+        &self.state
+    }
+    fn selector_mut(&mut self) -> &mut impl SelectorState {
+        &mut self.state
+    }
+    fn len(&self) -> usize {
+        self.contents_vec().len()
+    }
+}
+
+impl Searchable<HelpEntry> for HelpPopup {
+    fn filter(&self) -> &Filter {
+        &self.search
+    }
+    fn filter_mut(&mut self) -> &mut Filter {
+        &mut self.search
+    }
+    fn contents(&self) -> Box<dyn Iterator<Item = &HelpEntry> + '_> {
+        if self.should_filter() {
+            Box::new(
+                self.filter()
+                    .cache
+                    .order
+                    .iter()
+                    .filter_map(|idx| idx.map(|i| &self.contents[i])),
+            )
+        } else {
+            Box::new(self.contents.iter())
+        }
+    }
+    fn selected_item_mut(&mut self) -> Option<&mut HelpEntry> {
+        if self.should_filter() {
+            self.selector().selected().and_then(|i| {
+                self.search.cache.order[i]
+                    .and_then(|j| self.contents.get_mut(j))
+            })
+        } else {
+            self.selector()
+                .selected()
+                .and_then(|i| self.contents.get_mut(i))
+        }
+    }
+    fn update_filter_cache(
+        &mut self,
+        matcher: &mut Matcher,
+        top_k: Option<usize>,
+    ) {
+        if self.filter().cache.query == self.filter().query {
+            return;
+        }
+        if self.filter().cache.utfstrings_cache.is_none() {
+            self.filter_mut().cache.utfstrings_cache = Some(
+                self.contents
+                    .iter()
+                    .map(|i| Utf32String::from(i.to_search_string()))
+                    .collect(),
+            );
+        }
+        self.filter_mut().cache.query = self.filter().query.clone();
+        self.filter_mut().cache.order = compute_orders(
+            &self.filter().query,
+            self.filter().cache.utfstrings_cache.as_ref().unwrap(),
+            &[],
+            matcher,
+            0,
+        );
+
+        let strings_iterator = self
+            .filter()
+            .cache
+            .order
+            .iter()
+            .take_while(|i| i.is_some())
+            .map(|i| {
+                &self.filter().cache.utfstrings_cache.as_ref().unwrap()
+                    [i.unwrap()]
+            });
+        let strings: Vec<&Utf32String> = match top_k {
+            Some(k) => strings_iterator.take(k).collect(),
+            None => strings_iterator.collect(),
+        };
+        self.filter_mut().cache.indices =
+            compute_indices(&self.filter().query, strings, matcher);
+    }
+}
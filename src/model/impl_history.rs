@@ -0,0 +1,114 @@
+use super::*;
+use crate::update::Vertical;
+use platform_dirs::AppDirs;
+use std::fs;
+use toml::{Table, Value};
+
+/// How many queries each history keeps.
+const HISTORY_LEN: usize = 100;
+
+impl SearchHistory {
+    pub fn new(entries: Vec<String>) -> Self {
+        Self {
+            entries,
+            recalled: None,
+            changed: false,
+        }
+    }
+    /// Adds a submitted query as the most recent entry.
+    pub fn push(&mut self, query: &str) {
+        self.recalled = None;
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        self.entries.retain(|e| e != query);
+        self.entries.push(query.to_string());
+        self.changed = true;
+        if self.entries.len() > HISTORY_LEN {
+            self.entries.remove(0);
+        }
+    }
+    /// Replaces the query with an older entry going up, or a newer one going
+    /// down. Going up from an edited query starts at the newest entry.
+    pub fn recall(&mut self, query: &mut String, dir: &Vertical) {
+        let current = self
+            .recalled
+            .filter(|&i| self.entries.get(i).is_some_and(|e| e == query));
+        let next = match (dir, current) {
+            (Vertical::Up, Some(i)) => Some(i.saturating_sub(1)),
+            (Vertical::Up, None) if !self.entries.is_empty() => {
+                Some(self.entries.len() - 1)
+            }
+            (Vertical::Down, Some(i)) if i + 1 < self.entries.len() => {
+                Some(i + 1)
+            }
+            // past the newest entry, back to an empty query
+            (Vertical::Down, Some(_)) => None,
+            _ => return,
+        };
+        self.recalled = next;
+        *query = next.map(|i| self.entries[i].clone()).unwrap_or_default();
+    }
+}
+
+impl SearchHistories {
+    /// Reads the histories saved by the last session. A missing or broken
+    /// file just means starting without history.
+    pub fn load() -> Self {
+        let path = AppDirs::new(Some("inori"), true)
+            .map(|d| d.state_dir.join("search_history.toml"));
+        let table = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|c| c.parse::<Table>().ok())
+            .unwrap_or_default();
+        let read = |name: &str| {
+            let entries = match table.get(name) {
+                Some(Value::Array(a)) => a
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            SearchHistory::new(entries)
+        };
+        Self {
+            artist: read("artist"),
+            tracks: read("tracks"),
+            queue: read("queue"),
+            global: read("global"),
+            path,
+        }
+    }
+    /// Writes the histories to the state directory, if any of them changed.
+    pub fn save(&mut self) -> Result<()> {
+        let mut histories = [
+            ("artist", &mut self.artist),
+            ("tracks", &mut self.tracks),
+            ("queue", &mut self.queue),
+            ("global", &mut self.global),
+        ];
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        if !histories.iter().any(|(_, h)| h.changed) {
+            return Ok(());
+        }
+        let mut table = Table::new();
+        for (name, history) in histories.iter_mut() {
+            history.changed = false;
+            table.insert(
+                name.to_string(),
+                Value::Array(
+                    history.entries.iter().cloned().map(Value::from).collect(),
+                ),
+            );
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, table.to_string())?;
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ use search_utils::{compute_indices, compute_orders};
 impl LibraryState {
     pub fn new() -> Self {
         Self {
+            view: 0,
             artist_search: super::Filter::new(),
             global_search: GlobalSearchState {
                 contents: None,
@@ -15,8 +16,44 @@ impl LibraryState {
             active: super::LibActiveSelector::ArtistSelector,
             contents: Vec::new(),
             artist_state: ListState::default(),
+            marked: Vec::new(),
+            jump: None,
         }
     }
+
+    /// The mark corresponding to the selected row of the track selector.
+    pub fn selected_mark(&self) -> Option<LibraryMark> {
+        let artist = self.selected_item()?;
+        match artist.selected_item()?.item {
+            ItemRef::Album(a) => Some(LibraryMark::Album {
+                artist: artist.name.clone(),
+                album: a.values.clone(),
+            }),
+            ItemRef::Song(s) => Some(LibraryMark::Song(s.file.clone())),
+        }
+    }
+
+    /// Marks are kept in the order they were made, which is the order they
+    /// are added to the queue in.
+    pub fn toggle_mark(&mut self) {
+        if let Some(mark) = self.selected_mark() {
+            if let Some(i) = self.marked.iter().position(|m| *m == mark) {
+                self.marked.remove(i);
+            } else {
+                self.marked.push(mark);
+            }
+        }
+    }
+
+    pub fn is_marked(&self, artist: &str, item: &ItemRef) -> bool {
+        self.marked.iter().any(|m| match (m, item) {
+            (LibraryMark::Album { artist: ar, album }, ItemRef::Album(a)) => {
+                ar == artist && *album == a.values
+            }
+            (LibraryMark::Song(file), ItemRef::Song(s)) => *file == s.file,
+            _ => false,
+        })
+    }
 }
 
 impl Selector for LibraryState {
@@ -82,6 +119,7 @@ impl Searchable<ArtistData> for LibraryState {
         self.filter_mut().cache.order = compute_orders(
             &self.filter().query,
             self.filter().cache.utfstrings_cache.as_ref().unwrap(),
+            &[],
             matcher,
             0,
         );
@@ -0,0 +1,31 @@
+use super::*;
+use std::time::{Duration, Instant};
+
+/// How long a notification stays on screen.
+pub const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(4);
+const NOTIFICATION_HISTORY: usize = 32;
+
+impl Model {
+    pub fn notify(&mut self, text: impl Into<String>) {
+        self.push_notification(NotificationLevel::Info, text.into());
+    }
+    pub fn notify_error(&mut self, text: impl Into<String>) {
+        self.push_notification(NotificationLevel::Error, text.into());
+    }
+    fn push_notification(&mut self, level: NotificationLevel, text: String) {
+        if self.notifications.len() == NOTIFICATION_HISTORY {
+            self.notifications.pop_front();
+        }
+        self.notifications.push_back(Notification {
+            level,
+            text,
+            time: Instant::now(),
+        });
+    }
+    /// The latest notification, if it hasn't timed out yet.
+    pub fn current_notification(&self) -> Option<&Notification> {
+        self.notifications
+            .back()
+            .filter(|n| n.time.elapsed() < NOTIFICATION_TIMEOUT)
+    }
+}
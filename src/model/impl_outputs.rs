@@ -0,0 +1,36 @@
+use super::proto::*;
+use super::*;
+
+impl OutputsPopup {
+    pub fn new(contents: Vec<Output>) -> Self {
+        let mut popup = Self {
+            contents,
+            state: ListState::default(),
+        };
+        popup.init();
+        popup
+    }
+    pub fn selected_output(&self) -> Option<&Output> {
+        self.selected().and_then(|i| self.contents.get(i))
+    }
+    /// Replaces the outputs, keeping the selection on the same output id.
+    pub fn refresh(&mut self, contents: Vec<Output>) {
+        let id = self.selected_output().map(|o| o.id);
+        self.contents = contents;
+        let idx = self.contents.iter().position(|o| Some(o.id) == id);
+        self.set_selected(idx);
+        self.init();
+    }
+}
+
+impl Selector for OutputsPopup {
+    fn selector(&self) -> &impl SelectorState {
+        &self.state
+    }
+    fn selector_mut(&mut self) -> &mut impl SelectorState {
+        &mut self.state
+    }
+    fn len(&self) -> usize {
+        self.contents.len()
+    }
+}
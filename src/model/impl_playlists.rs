@@ -0,0 +1,139 @@
+use super::proto::*;
+use super::*;
+use nucleo_matcher::Matcher;
+use search_utils::{compute_indices, compute_orders};
+
+impl PlaylistsState {
+    pub fn new() -> Self {
+        Self {
+            search: Filter::new(),
+            active: PlaylistActiveSelector::PlaylistSelector,
+            fetched: false,
+            contents: Vec::new(),
+            playlist_state: ListState::default(),
+        }
+    }
+    pub fn selected_name(&self) -> Option<String> {
+        self.selected_item().map(|p| p.name.clone())
+    }
+    pub fn select_by_name(&mut self, name: &str) {
+        let idx = self.contents().position(|p| p.name == name);
+        if idx.is_some() {
+            self.set_selected(idx);
+        }
+    }
+}
+
+impl PlaylistData {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            fetched: false,
+            tracks: Vec::new(),
+            track_state: TableState::default(),
+        }
+    }
+    pub fn selected_track(&self) -> Option<&Song> {
+        self.selected().and_then(|i| self.tracks.get(i))
+    }
+}
+
+impl Selector for PlaylistData {
+    fn selector(&self) -> &impl SelectorState {
+        &self.track_state
+    }
+    fn selector_mut(&mut self) -> &mut impl SelectorState {
+        &mut self.track_state
+    }
+    fn len(&self) -> usize {
+        self.tracks.len()
+    }
+}
+
+impl Selector for PlaylistsState {
+    fn selector(&self) -> &impl SelectorState {
+        &self.playlist_state
+    }
+    fn selector_mut(&mut self) -> &mut impl SelectorState {
+        &mut self.playlist_state
+    }
+    fn len(&self) -> usize {
+        self.contents_vec().len()
+    }
+}
+
+impl Searchable<PlaylistData> for PlaylistsState {
+    fn filter(&self) -> &Filter {
+        &self.search
+    }
+    fn filter_mut(&mut self) -> &mut Filter {
+        &mut self.search
+    }
+    fn contents(&self) -> Box<dyn Iterator<Item = &PlaylistData> + '_> {
+        if self.should_filter() {
+            Box::new(
+                self.filter()
+                    .cache
+                    .order
+                    .iter()
+                    .filter_map(|idx| idx.map(|i| &self.contents[i])),
+            )
+        } else {
+            Box::new(self.contents.iter())
+        }
+    }
+    fn selected_item_mut(&mut self) -> Option<&mut PlaylistData> {
+        if self.should_filter() {
+            self.selector().selected().and_then(|i| {
+                self.search.cache.order[i]
+                    .and_then(|j| self.contents.get_mut(j))
+            })
+        } else {
+            self.selector()
+                .selected()
+                .and_then(|i| self.contents.get_mut(i))
+        }
+    }
+    fn update_filter_cache(
+        &mut self,
+        matcher: &mut Matcher,
+        top_k: Option<usize>,
+    ) {
+        if self.filter().cache.query == self.filter().query {
+            return;
+        }
+        if self.filter().cache.utfstrings_cache.is_none() {
+            self.filter_mut().cache.utfstrings_cache = Some(
+                self.contents
+                    .iter()
+                    .map(|i| Utf32String::from(i.name.clone()))
+                    .collect(),
+            );
+        }
+        self.filter_mut().cache.query = self.filter().query.clone();
+        self.filter_mut().cache.order = compute_orders(
+            &self.filter().query,
+            self.filter().cache.utfstrings_cache.as_ref().unwrap(),
+            &[],
+            matcher,
+            0,
+        );
+
+        let strings_iterator = self
+            .filter()
+            .cache
+            .order
+            .iter()
+            .take_while(|i| i.is_some())
+            .map(|i| {
+                &self.filter().cache.utfstrings_cache.as_ref().unwrap()
+                    [i.unwrap()]
+            });
+        let strings: Vec<&Utf32String> = match top_k {
+            Some(k) => strings_iterator.take(k).collect(),
+            None => strings_iterator.collect(),
+        };
+        self.filter_mut().cache.indices =
+            compute_indices(&self.filter().query, strings, matcher);
+    }
+}
@@ -0,0 +1,42 @@
+use super::*;
+
+impl Prompt {
+    pub fn new(kind: PromptKind) -> Self {
+        let input = match &kind {
+            PromptKind::RenamePlaylist(name) => name.clone(),
+            _ => String::new(),
+        };
+        Self { kind, input }
+    }
+    /// Whether the prompt is a yes/no question rather than a text field.
+    pub fn is_confirm(&self) -> bool {
+        matches!(
+            self.kind,
+            PromptKind::DeletePlaylist(_) | PromptKind::OverwritePlaylist(_)
+        )
+    }
+    pub fn clear_completions(&mut self) {
+        if let PromptKind::Command { completions } = &mut self.kind {
+            completions.clear();
+        }
+    }
+    pub fn title(&self) -> String {
+        match &self.kind {
+            PromptKind::RenamePlaylist(name) => {
+                format!("Rename playlist \"{}\"", name)
+            }
+            PromptKind::DeletePlaylist(name) => {
+                format!("Delete playlist \"{}\"? [y/n]", name)
+            }
+            PromptKind::SaveQueue => "Save queue as playlist".into(),
+            PromptKind::OverwritePlaylist(name) => {
+                format!("Overwrite playlist \"{}\"? [y/n]", name)
+            }
+            PromptKind::MoveTo => "Move selection to position".into(),
+            PromptKind::SetVolume => {
+                "Set volume (0-100, or +/- to adjust)".into()
+            }
+            PromptKind::Command { .. } => "Command".into(),
+        }
+    }
+}
@@ -65,9 +65,12 @@ impl Searchable<Song> for QueueSelector {
                     .collect(),
             );
         }
-        self.filter_mut().cache.order = search_utils::compute_orders(
-            &self.filter().query,
-            self.filter().cache.utfstrings_cache.as_ref().unwrap(),
+        let songs: Vec<Option<&Song>> =
+            self.contents.iter().map(Some).collect();
+        self.search.cache.order = search_utils::compute_orders(
+            &self.search.query,
+            self.search.cache.utfstrings_cache.as_ref().unwrap(),
+            &songs,
             matcher,
             0,
         );
@@ -80,6 +83,85 @@ impl QueueSelector {
             search: Filter::new(),
             contents: Vec::new(),
             state: TableState::default(),
+            marked: HashSet::new(),
+            visual_anchor: None,
+        }
+    }
+
+    /// Ids of every marked song, including the active visual range.
+    pub fn marked_ids(&self) -> HashSet<u32> {
+        let mut ids = self.marked.clone();
+        if let (Some(anchor), Some(to)) = (self.visual_anchor, self.selected())
+        {
+            let from = self
+                .contents()
+                .position(|s| s.place.is_some_and(|p| p.id.0 == anchor));
+            if let Some(from) = from {
+                ids.extend(
+                    self.contents()
+                        .skip(from.min(to))
+                        .take(from.abs_diff(to) + 1)
+                        .filter_map(|s| s.place.map(|p| p.id.0)),
+                );
+            }
+        }
+        ids
+    }
+
+    /// The songs an action applies to, in queue order: every marked song
+    /// and the visual range, or `count` songs from the selected one if
+    /// nothing is marked.
+    pub fn selection(&self, count: usize) -> Vec<&Song> {
+        let ids = self.marked_ids();
+        let mut songs: Vec<&Song> = self
+            .contents
+            .iter()
+            .filter(|s| s.place.is_some_and(|p| ids.contains(&p.id.0)))
+            .collect();
+        if songs.is_empty() {
+            if let Some(sel) = self.selected() {
+                songs.extend(self.contents().skip(sel).take(count));
+            }
+        }
+        songs.sort_by_key(|s| s.place.map(|p| p.pos));
+        songs
+    }
+
+    pub fn toggle_mark(&mut self) {
+        if let Some(id) = self.selected_item().and_then(|s| s.place) {
+            if !self.marked.remove(&id.id.0) {
+                self.marked.insert(id.id.0);
+            }
+        }
+    }
+
+    /// Starts a visual selection at the selected song, or ends the current
+    /// one, keeping its songs marked.
+    pub fn toggle_visual(&mut self) {
+        if self.visual_anchor.is_some() {
+            self.marked = self.marked_ids();
+            self.visual_anchor = None;
+        } else {
+            self.visual_anchor =
+                self.selected_item().and_then(|s| s.place).map(|p| p.id.0);
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.visual_anchor = None;
+    }
+
+    /// Forgets marks on songs that have left the queue.
+    pub fn retain_marks(&mut self) {
+        let ids: HashSet<u32> = self
+            .contents
+            .iter()
+            .filter_map(|s| s.place.map(|p| p.id.0))
+            .collect();
+        self.marked.retain(|id| ids.contains(id));
+        if self.visual_anchor.is_some_and(|id| !ids.contains(&id)) {
+            self.visual_anchor = None;
         }
     }
 }
@@ -159,6 +159,7 @@ impl Searchable<InfoEntry> for GlobalSearchState {
         self.filter_mut().cache.order = compute_orders(
             &self.filter().query,
             self.filter().cache.utfstrings_cache.as_ref().unwrap(),
+            &[],
             matcher,
             0,
         );
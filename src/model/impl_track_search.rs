@@ -0,0 +1,57 @@
+use super::proto::*;
+use super::*;
+
+impl TrackSearchState {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            dirty: false,
+            typed_at: Instant::now(),
+            contents: Vec::new(),
+            state: TableState::default(),
+        }
+    }
+    pub fn selected_song(&self) -> Option<&Song> {
+        self.selected().and_then(|i| self.contents.get(i))
+    }
+    /// Returns the query to send once it has been left alone for `delay`.
+    pub fn take_query(&mut self, delay: Duration) -> Option<String> {
+        if !self.dirty || self.typed_at.elapsed() < delay {
+            return None;
+        }
+        self.dirty = false;
+        Some(self.query.trim().to_string())
+    }
+    /// How long until the query is due, if it has to be sent.
+    pub fn due_in(&self, delay: Duration) -> Option<Duration> {
+        self.dirty
+            .then(|| delay.saturating_sub(self.typed_at.elapsed()))
+    }
+    /// Shows the results of `query`, unless it has been edited since.
+    pub fn set_results(&mut self, query: &str, songs: Vec<Song>) {
+        if query != self.query.trim() {
+            return;
+        }
+        self.contents = songs;
+        self.set_selected(None);
+        self.init();
+    }
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.dirty = false;
+        self.contents.clear();
+        self.set_selected(None);
+    }
+}
+
+impl Selector for TrackSearchState {
+    fn selector(&self) -> &impl SelectorState {
+        &self.state
+    }
+    fn selector_mut(&mut self) -> &mut impl SelectorState {
+        &mut self.state
+    }
+    fn len(&self) -> usize {
+        self.contents.len()
+    }
+}
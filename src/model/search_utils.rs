@@ -1,22 +1,273 @@
-use nucleo_matcher::pattern::{AtomKind, CaseMatching, Normalization, Pattern};
+use mpd::Song;
+use nucleo_matcher::pattern::{Atom, AtomKind, CaseMatching, Normalization};
 use nucleo_matcher::Matcher;
 use nucleo_matcher::Utf32String;
+use std::cmp::Ordering;
 
+/// A song tag a search term can be scoped to with `field:term`.
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Artist,
+    AlbumArtist,
+    Album,
+    Title,
+    Genre,
+    Composer,
+    Date,
+    Year,
+    File,
+}
+
+const FIELDS: [(&str, Field); 9] = [
+    ("artist", Field::Artist),
+    ("albumartist", Field::AlbumArtist),
+    ("album", Field::Album),
+    ("title", Field::Title),
+    ("genre", Field::Genre),
+    ("composer", Field::Composer),
+    ("date", Field::Date),
+    ("year", Field::Year),
+    ("file", Field::File),
+];
+
+impl Field {
+    fn values(self, song: &Song) -> Vec<&str> {
+        let tag = |name: &str| -> Vec<&str> {
+            song.tags
+                .iter()
+                .filter(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+                .collect()
+        };
+        match self {
+            Field::Artist => song.artist.as_deref().into_iter().collect(),
+            Field::AlbumArtist => tag("AlbumArtist"),
+            Field::Album => tag("Album"),
+            Field::Title => song.title.as_deref().into_iter().collect(),
+            Field::Genre => tag("Genre"),
+            Field::Composer => tag("Composer"),
+            Field::Date | Field::Year => tag("Date"),
+            Field::File => vec![song.file.as_str()],
+        }
+    }
+}
+
+enum Matching {
+    Text(Atom),
+    /// A year compared with the leading year of the date tag.
+    Year(Ordering, bool, i64),
+}
+
+struct Term {
+    negative: bool,
+    field: Option<Field>,
+    matching: Matching,
+}
+
+/// A search query. Its whitespace separated terms must all match:
+/// - `foo` matches fuzzily, `^foo` and `foo$` anchor it to the start or end
+/// - `"foo bar"` matches the exact phrase
+/// - `!foo` excludes entries that contain foo
+/// - `field:foo` matches against one tag of a song, and `year:>1990`
+///   compares the year of its date
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Self {
+        Self {
+            terms: split_terms(query)
+                .iter()
+                .filter_map(|t| parse_term(t))
+                .collect(),
+        }
+    }
+
+    /// Scores an entry by its text, or by the tags of its song for terms
+    /// scoped to a field. Entries without a song match scoped terms against
+    /// their text instead.
+    fn score(
+        &self,
+        text: &Utf32String,
+        song: Option<&Song>,
+        matcher: &mut Matcher,
+    ) -> Option<u32> {
+        let mut total: u32 = 0;
+        for term in &self.terms {
+            let score = match (term.field, song) {
+                (Some(field), Some(song)) => field
+                    .values(song)
+                    .into_iter()
+                    .filter_map(|v| {
+                        term.score(&Utf32String::from(v), Some(v), matcher)
+                    })
+                    .max(),
+                _ => term.score(text, None, matcher),
+            };
+            match (score, term.negative) {
+                (Some(_), true) | (None, false) => return None,
+                (Some(score), false) => total += u32::from(score),
+                (None, true) => {}
+            }
+        }
+        Some(total)
+    }
+}
+
+impl Term {
+    fn score(
+        &self,
+        haystack: &Utf32String,
+        raw: Option<&str>,
+        matcher: &mut Matcher,
+    ) -> Option<u16> {
+        match &self.matching {
+            Matching::Text(atom) => atom.score(haystack.slice(..), matcher),
+            Matching::Year(ord, or_equal, year) => {
+                let date = raw?;
+                let found: i64 = date
+                    .get(..4)
+                    .filter(|y| y.bytes().all(|b| b.is_ascii_digit()))?
+                    .parse()
+                    .ok()?;
+                let cmp = found.cmp(year);
+                (cmp == *ord || (*or_equal && cmp == Ordering::Equal))
+                    .then_some(0)
+            }
+        }
+    }
+}
+
+/// Splits a query on whitespace outside of double quotes.
+fn split_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    terms
+}
+
+/// Parses a term, or returns `None` while it has nothing to match yet.
+fn parse_term(term: &str) -> Option<Term> {
+    let (negative, rest) = match term.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, term),
+    };
+    let (field, value) = match rest.split_once(':').and_then(|(name, value)| {
+        let field =
+            FIELDS.iter().find(|(n, _)| n.eq_ignore_ascii_case(name))?;
+        Some((field.1, value))
+    }) {
+        Some((field, value)) => (Some(field), value),
+        // not a field, like the colon in 1:30
+        None => (None, rest),
+    };
+    // field:!foo is the same as !field:foo
+    let (negative, value) = match value.strip_prefix('!') {
+        Some(value) if field.is_some() => (true, value),
+        _ => (negative, value),
+    };
+    if field == Some(Field::Year) {
+        if let Some(matching) = parse_year(value) {
+            return Some(Term {
+                negative,
+                field,
+                matching,
+            });
+        }
+    }
+    let atom = match value
+        .strip_prefix('"')
+        .map(|v| v.strip_suffix('"').unwrap_or(v))
+    {
+        Some(phrase) => Atom::new(
+            phrase,
+            CaseMatching::Ignore,
+            Normalization::Smart,
+            AtomKind::Substring,
+            false,
+        ),
+        None if negative => {
+            let mut atom = Atom::parse(
+                &format!("!{}", value),
+                CaseMatching::Ignore,
+                Normalization::Smart,
+            );
+            // the term handles negation, so that it applies to all values
+            // of a tag
+            atom.negative = false;
+            atom
+        }
+        None => Atom::parse(value, CaseMatching::Ignore, Normalization::Smart),
+    };
+    if atom.needle_text().is_empty() {
+        return None;
+    }
+    Some(Term {
+        negative,
+        field,
+        matching: Matching::Text(atom),
+    })
+}
+
+fn parse_year(value: &str) -> Option<Matching> {
+    let (ord, or_equal, year) = if let Some(y) = value.strip_prefix(">=") {
+        (Ordering::Greater, true, y)
+    } else if let Some(y) = value.strip_prefix("<=") {
+        (Ordering::Less, true, y)
+    } else if let Some(y) = value.strip_prefix('>') {
+        (Ordering::Greater, false, y)
+    } else if let Some(y) = value.strip_prefix('<') {
+        (Ordering::Less, false, y)
+    } else {
+        (
+            Ordering::Equal,
+            true,
+            value.strip_prefix('=').unwrap_or(value),
+        )
+    };
+    Some(Matching::Year(ord, or_equal, year.parse().ok()?))
+}
+
+/// Orders the entries by how well they match the query. `songs` holds the
+/// song behind each entry, if any, for terms scoped to a field. It may be
+/// empty for lists that aren't songs.
 pub fn compute_orders(
     query: &str,
     strings: &[Utf32String],
+    songs: &[Option<&Song>],
     matcher: &mut Matcher,
     score_threshold: u32,
 ) -> Vec<Option<usize>> {
-    let pattern = Pattern::new(
-        query,
-        CaseMatching::Ignore,
-        Normalization::Smart,
-        AtomKind::Fuzzy,
-    );
+    let query = Query::parse(query);
+    if query.terms.is_empty() {
+        return vec![None; strings.len()];
+    }
     let scores = strings
         .iter()
-        .map(|i| pattern.score(i.slice(..), matcher))
+        .enumerate()
+        .map(|(i, s)| {
+            let song = songs.get(i).copied().flatten();
+            // a match of terms that don't score, like !foo, still counts
+            query.score(s, song, matcher).map(|score| score.max(1))
+        })
         .collect::<Vec<Option<u32>>>();
     let mut order = scores
         .into_iter()
@@ -36,22 +287,121 @@ pub fn compute_orders(
     order
 }
 
+/// The characters to highlight in each string, for the terms that match
+/// the whole entry rather than a field.
 pub fn compute_indices(
     query: &str,
     strings: Vec<&Utf32String>,
     matcher: &mut Matcher,
 ) -> Vec<Vec<u32>> {
-    let pattern = Pattern::new(
-        query,
-        CaseMatching::Ignore,
-        Normalization::Smart,
-        AtomKind::Fuzzy,
-    );
+    let query = Query::parse(query);
     let mut indices: Vec<Vec<u32>> = Vec::new();
     for s in strings {
         let mut tmp: Vec<u32> = Vec::new();
-        pattern.indices(s.slice(..), matcher, &mut tmp);
+        for term in &query.terms {
+            if let (false, None, Matching::Text(atom)) =
+                (term.negative, term.field, &term.matching)
+            {
+                atom.indices(s.slice(..), matcher, &mut tmp);
+            }
+        }
+        tmp.sort_unstable();
+        tmp.dedup();
         indices.push(tmp);
     }
     indices
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song() -> Song {
+        Song {
+            file: "pink floyd/animals/dogs.flac".into(),
+            title: Some("Dogs".into()),
+            artist: Some("Pink Floyd".into()),
+            tags: vec![
+                ("Album".into(), "Animals".into()),
+                ("Date".into(), "1977-01-23".into()),
+            ],
+            ..Song::default()
+        }
+    }
+
+    fn matches(query: &str, text: &str, song: Option<&Song>) -> bool {
+        let mut matcher = Matcher::new(nucleo_matcher::Config::DEFAULT);
+        Query::parse(query)
+            .score(&Utf32String::from(text), song, &mut matcher)
+            .is_some()
+    }
+
+    #[test]
+    fn plain_terms_match_fuzzily() {
+        assert!(matches("pnkfld", "Pink Floyd", None));
+        assert!(matches("pink dogs", "Pink Floyd - Dogs", None));
+        assert!(!matches("pink sheep", "Pink Floyd - Dogs", None));
+    }
+
+    #[test]
+    fn anchors() {
+        assert!(matches("^pink", "Pink Floyd", None));
+        assert!(!matches("^floyd", "Pink Floyd", None));
+        assert!(matches("floyd$", "Pink Floyd", None));
+        assert!(!matches("pink$", "Pink Floyd", None));
+    }
+
+    #[test]
+    fn quoted_phrases() {
+        assert!(matches("\"pink floyd\"", "Pink Floyd", None));
+        assert!(!matches("\"floyd pink\"", "Pink Floyd", None));
+        assert!(!matches("\"pnk\"", "Pink Floyd", None));
+        // an unterminated quote runs to the end of the query
+        assert!(matches("\"pink floyd", "Pink Floyd", None));
+        assert_eq!(split_terms("\"a b c"), vec!["\"a b c"]);
+    }
+
+    #[test]
+    fn excluded_terms() {
+        assert!(matches("!dogs", "Pink Floyd - Sheep", None));
+        assert!(!matches("!dogs", "Pink Floyd - Dogs", None));
+        assert!(parse_term("!").is_none());
+    }
+
+    #[test]
+    fn fields() {
+        let song = song();
+        let text = song.file.clone();
+        assert!(matches("title:dogs", &text, Some(&song)));
+        assert!(!matches("title:floyd", &text, Some(&song)));
+        assert!(matches("album:animals", &text, Some(&song)));
+        assert!(!matches("artist:!floyd", &text, Some(&song)));
+        assert!(matches("artist:!queen", &text, Some(&song)));
+        assert!(!matches("!artist:floyd", &text, Some(&song)));
+        // entries without a song match scoped terms against their text
+        assert!(matches("artist:pink", "Pink Floyd", None));
+    }
+
+    #[test]
+    fn colons_outside_fields_are_text() {
+        let term = parse_term("1:30").unwrap();
+        assert!(term.field.is_none());
+        assert!(matches("1:30", "track 1:30", None));
+    }
+
+    #[test]
+    fn years() {
+        let song = song();
+        let text = song.file.clone();
+        assert!(matches("year:>1970", &text, Some(&song)));
+        assert!(!matches("year:>1977", &text, Some(&song)));
+        assert!(matches("year:>=1977", &text, Some(&song)));
+        assert!(matches("year:<1980", &text, Some(&song)));
+        assert!(!matches("year:<1977", &text, Some(&song)));
+        assert!(matches("year:<=1977", &text, Some(&song)));
+        assert!(matches("year:=1977", &text, Some(&song)));
+        assert!(matches("year:1977", &text, Some(&song)));
+        assert!(!matches("year:=1978", &text, Some(&song)));
+        assert!(!matches("year:!1977", &text, Some(&song)));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::cli::Remote;
+use crate::config::{Config, LibraryView};
+use crate::event_handler::{Event, Result};
+use crate::model::{InfoEntry, Model, NotificationLevel, Screen};
+use crate::update::{self, Message};
+use crate::util::{format_time, song_album};
+use mpd::State::*;
+use ratatui::layout::Rect;
+use std::sync::mpsc::{self, Receiver};
+
+const SONG_FORMAT: &str = "%artist% - %title%
+[%state%] #%position%/%length% %elapsed%/%duration%";
+const OPTIONS_FORMAT: &str = "volume: %volume% repeat: %repeat% \
+random: %random% single: %single% consume: %consume%";
+
+/// Runs a single command against mpd, the same way the interface would,
+/// and reports errors instead of showing them.
+pub fn run(mut config: Config, remote: Remote) -> Result<()> {
+    // `add` looks up album artists, whatever the library views are
+    config.library_views = vec![LibraryView::album_artists()];
+    let (tx, rx) = mpsc::channel();
+    let mut model = Model::new(Rect::default(), tx, config)?;
+    model.screen = Screen::Library;
+    match remote {
+        Remote::Msg(m) => dispatch(&mut model, m)?,
+        Remote::Volume(v) => {
+            let update = update::set_volume(&mut model, &v)?;
+            if update.is_empty() {
+                return Err(format!("invalid volume {}", v).into());
+            }
+        }
+        Remote::Add(artist) => {
+            model.request_library();
+            wait_for_worker(&mut model, &rx)?;
+            let name = model
+                .library
+                .contents
+                .iter()
+                .map(|a| &a.name)
+                .find(|name| name.to_lowercase() == artist.to_lowercase())
+                .cloned()
+                .ok_or_else(|| format!("no album artist named {}", artist))?;
+            model.jump_to(InfoEntry {
+                artist: name,
+                artist_sort: None,
+                album: None,
+                title: None,
+            });
+            dispatch(&mut model, Message::Select)?;
+        }
+        Remote::Status(format) => {
+            model.update_currentsong()?;
+            println!("{}", format_status(&model, format.as_deref()));
+        }
+    }
+    wait_for_worker(&mut model, &rx)?;
+    match model
+        .notifications
+        .iter()
+        .find(|n| matches!(n.level, NotificationLevel::Error))
+    {
+        Some(n) => Err(n.text.clone().into()),
+        None => Ok(()),
+    }
+}
+
+fn dispatch(model: &mut Model, m: Message) -> Result<()> {
+    let update = update::handle_msg(model, m)?;
+    update::update_screens(model, update)
+}
+
+fn wait_for_worker(model: &mut Model, rx: &Receiver<Event>) -> Result<()> {
+    while model.worker.is_busy() {
+        if let Event::Worker(response) = rx.recv()? {
+            update::handle_response(model, response)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the `%name%` placeholders in the format with the status. Without
+/// a format, the song lines are left out while nothing is playing.
+fn format_status(model: &Model, format: Option<&str>) -> String {
+    let format = match (format, &model.currentsong) {
+        (Some(format), _) => format.to_string(),
+        (None, Some(_)) => format!("{}\n{}", SONG_FORMAT, OPTIONS_FORMAT),
+        (None, None) => format!("[%state%]\n{}", OPTIONS_FORMAT),
+    };
+    let mut out = String::new();
+    let mut rest = format.as_str();
+    while let Some((before, after)) = rest.split_once('%') {
+        out.push_str(before);
+        match after.split_once('%').and_then(|(name, after_name)| {
+            Some((placeholder(model, name)?, after_name))
+        }) {
+            Some((value, after_name)) => {
+                out.push_str(&value);
+                rest = after_name;
+            }
+            None => {
+                // not a placeholder, so keep the % as it is
+                out.push('%');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn placeholder(model: &Model, name: &str) -> Option<String> {
+    let status = &model.status;
+    let song = model.currentsong.as_ref();
+    let tag = |name: &str| {
+        song.and_then(|s| s.tags.iter().find(|t| t.0 == name))
+            .map(|t| t.1.clone())
+            .unwrap_or_default()
+    };
+    let flag = |b: bool| if b { "on" } else { "off" }.to_string();
+    Some(match name {
+        "" => "%".to_string(),
+        "artist" => song.and_then(|s| s.artist.clone()).unwrap_or_default(),
+        "albumartist" => tag("AlbumArtist"),
+        "album" => song.and_then(song_album).cloned().unwrap_or_default(),
+        "title" => song.and_then(|s| s.title.clone()).unwrap_or_default(),
+        "date" => tag("Date"),
+        "file" => song.map(|s| s.file.clone()).unwrap_or_default(),
+        "state" => match status.state {
+            Play => "playing",
+            Pause => "paused",
+            Stop => "stopped",
+        }
+        .to_string(),
+        "position" => status
+            .song
+            .map(|s| (s.pos + 1).to_string())
+            .unwrap_or_default(),
+        "length" => status.queue_len.to_string(),
+        "elapsed" => status.elapsed.map(format_time).unwrap_or_default(),
+        "duration" => status.duration.map(format_time).unwrap_or_default(),
+        "volume" if status.volume < 0 => "-".to_string(),
+        "volume" => format!("{}%", status.volume),
+        "repeat" => flag(status.repeat),
+        "random" => flag(status.random),
+        "single" => flag(status.single),
+        "consume" => flag(status.consume),
+        _ => return None,
+    })
+}
@@ -1,26 +1,40 @@
-use crate::config::keybind::{KeybindMap, KeybindTarget};
+use crate::config::keybind::{KeyContext, KeybindTarget, Keybindings};
 use crate::event_handler::Result;
 use crate::model::proto::Searchable;
-use crate::model::{Model, Screen, State};
+use crate::model::{
+    ConnectionState, HelpPopup, Model, OutputsPopup, Prompt, PromptKind,
+    Screen, State,
+};
+use crate::worker::{Request, Response, Songs};
 use bitflags::bitflags;
 use mpd::status::State as PlayState;
 use mpd::Subsystem;
 use ratatui::crossterm::event::{self, KeyCode, KeyEvent};
 use std::option::Option;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+pub mod build_files;
 pub mod build_library;
+pub mod build_playlists;
 mod handlers;
 mod updaters;
+pub use handlers::prompt_handler::set_volume;
+use handlers::SearchKey;
+
+const MAX_COUNT: usize = 99999;
+const TRACK_SEARCH_DELAY: Duration = Duration::from_millis(300);
+// mpd closes idle clients after connection_timeout, 60s by default
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
 
 bitflags! {
-    pub struct Update: u8 {
+    pub struct Update: u16 {
         const QUEUE = 0b00000001;
         const CURRENT_ARTIST = 0b00000010;
         const STATUS = 0b00000100;
         const CURRENT_SONG = 0b00001000;
         const START_PLAYING = 0b00010000;
-        const IDLE_UPDATES = 0b00100000;
+        const PLAYLISTS = 0b00100000;
+        const OUTPUTS = 0b01000000;
     }
 }
 
@@ -50,7 +64,7 @@ pub enum SearchMsg {
     End,
 }
 
-#[derive(Clone, Debug)]
+#[derive(PartialEq, Clone, Debug)]
 pub enum Toggle {
     Repeat,
     Random,
@@ -65,11 +79,12 @@ pub enum SeekDirection {
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(PartialEq, Clone, Debug)]
 pub enum Message {
     Direction(Dirs),
     ScrollScreenful(Vertical),
     PlayPause,
+    Play,
     NextSong,
     PreviousSong,
     Seek(SeekDirection),
@@ -84,35 +99,170 @@ pub enum Message {
     Fold,
     Clear,
     LocalSearch(SearchMsg),
+    History(Vertical),
     GlobalSearch(SearchMsg),
     Escape,
     Set(Toggle),
+    Rename,
+    Replace,
+    SaveQueue,
+    ToggleMark,
+    Visual,
+    MoveTo,
+    InsertNext,
+    PlayNow,
+    MoveUp,
+    MoveDown,
+    NextLibraryView,
+    VolumeUp,
+    VolumeDown,
+    SetVolume,
+    Outputs,
+    CommandLine,
+    Help,
 }
 
 pub fn update_tick(model: &mut Model) -> Result<()> {
-    update_screens(model, Update::all() - Update::START_PLAYING)?;
+    if let ConnectionState::Disconnected { .. } = model.connection {
+        if model.poll_reconnect() {
+            rebuild(model)?;
+        }
+        return Ok(());
+    }
+    model.extrapolate_elapsed();
+    if let Some(timeout) = model.config.keybinding_timeout {
+        if model.parse_state_at.elapsed() >= timeout {
+            model.parse_state.clear();
+            model.parse_count = None;
+        }
+    }
+    send_track_search(model, TRACK_SEARCH_DELAY);
+    if let Err(e) = model.history.save() {
+        model.notify_error(format!("couldn't save search history: {}", e));
+    }
+    if model.pinged_at.elapsed() >= KEEPALIVE_INTERVAL {
+        model.pinged_at = Instant::now();
+        model.conn.ping()?;
+    }
     Ok(())
 }
 
-pub fn update_screens(model: &mut Model, mut update: Update) -> Result<()> {
-    if update.contains(Update::IDLE_UPDATES) {
-        let changes = model.idle_conn.get()?;
-        if changes.contains(&Subsystem::Database) {
-            build_library::build_library(model)?;
+// the event loop waits no longer than this for the next event
+pub fn timeout(model: &Model) -> Option<Duration> {
+    model.track_search.due_in(TRACK_SEARCH_DELAY)
+}
+
+pub fn update_timeout(model: &mut Model) -> Result<()> {
+    send_track_search(model, TRACK_SEARCH_DELAY);
+    Ok(())
+}
+
+fn send_track_search(model: &mut Model, delay: Duration) {
+    match model.track_search.take_query(delay) {
+        Some(query) if query.is_empty() => model.track_search.clear(),
+        Some(query) => model.worker.request(Request::TrackSearch(query)),
+        None => {}
+    }
+}
+
+// startup and reconnects: anything may have changed
+pub fn rebuild(model: &mut Model) -> Result<()> {
+    model.request_library();
+    model.library.global_search.contents = None;
+    model.playlists.fetched = false;
+    model.files.fetched = false;
+    update_screens(model, Update::all() - Update::START_PLAYING)
+}
+
+pub fn handle_response(model: &mut Model, response: Response) -> Result<()> {
+    let mut update = Update::empty();
+    match response {
+        Response::DBUpdated => {
+            model.worker.finish(&Request::UpdateDB);
+            model.notify("database updated");
         }
-        if changes.contains(&Subsystem::Update)
-            || changes.contains(&Subsystem::Options)
-        {
-            update |= Update::STATUS;
+        Response::Library(tag, entries) => {
+            model.worker.finish(&Request::Library(tag.clone()));
+            // the view may have changed while the worker was busy
+            if tag == model.library_view().tags[0] {
+                build_library::build_library(model, &tag, entries);
+            }
+        }
+        Response::GlobalSearch(entries) => {
+            model.worker.finish(&Request::GlobalSearch);
+            model.set_global_search_contents(entries);
+            model
+                .library
+                .global_search
+                .update_filter_cache(&mut model.matcher, None);
+        }
+        Response::TrackSearch(query, songs) => {
+            model.worker.finish(&Request::TrackSearch(query.clone()));
+            model.track_search.set_results(&query, songs);
+        }
+        Response::Tracks(tags, name, songs) => {
+            build_library::set_tracks(model, &tags, &name, songs);
+            model.finish_jump(&name);
+            model.worker.finish(&Request::Tracks(tags, name));
+        }
+        Response::Inserted(request, n) => {
+            model.worker.finish(&request);
+            model.notify(added_message(n));
+            update = Update::STATUS
+                | Update::QUEUE
+                | Update::START_PLAYING
+                | Update::CURRENT_SONG;
         }
+        Response::Failed(request, e) => {
+            model.worker.finish(&request);
+            model.notify_error(e);
+        }
+    }
+    update_screens(model, update)
+}
+
+fn added_message(n: usize) -> String {
+    match n {
+        1 => "added 1 song".to_string(),
+        n => format!("added {} songs", n),
+    }
+}
+
+pub fn handle_idle(model: &mut Model, changes: &[Subsystem]) -> Result<()> {
+    if let ConnectionState::Disconnected { .. } = model.connection {
+        return Ok(());
     }
+    let mut update = Update::empty();
+    for subsystem in changes {
+        match subsystem {
+            Subsystem::Database => {
+                model.request_library();
+                model.library.global_search.contents = None;
+                model.files.fetched = false;
+            }
+            Subsystem::Queue => update |= Update::QUEUE | Update::STATUS,
+            Subsystem::Player => {
+                update |= Update::STATUS | Update::CURRENT_SONG
+            }
+            Subsystem::Update | Subsystem::Mixer | Subsystem::Options => {
+                update |= Update::STATUS
+            }
+            Subsystem::Playlist => update |= Update::PLAYLISTS,
+            Subsystem::Output => update |= Update::OUTPUTS,
+            _ => {}
+        }
+    }
+    update_screens(model, update)
+}
+
+pub fn update_screens(model: &mut Model, update: Update) -> Result<()> {
     if update.contains(Update::QUEUE) {
         model.queue.contents = model.conn.queue().unwrap_or_default();
     }
     if update.contains(Update::CURRENT_ARTIST)
         && model.library.selected_item_mut().is_some()
     {
-        build_library::add_tracks(model)?;
+        build_library::add_tracks(model);
     }
     if update.contains(Update::START_PLAYING) {
         if !update.contains(Update::QUEUE) {
@@ -129,10 +279,26 @@ pub fn update_screens(model: &mut Model, mut update: Update) -> Result<()> {
     }
     if update.contains(Update::STATUS) {
         model.update_status()?;
+        if let Some(before) = model.added_from.take() {
+            let n = model.status.queue_len.saturating_sub(before);
+            model.notify(added_message(n as usize));
+        }
+    }
+    if update.contains(Update::PLAYLISTS) && model.playlists.fetched {
+        build_playlists::build_playlists(model)?;
+    }
+    if update.contains(Update::OUTPUTS) && model.outputs.is_some() {
+        let outputs = model.conn.outputs()?;
+        if let Some(popup) = model.outputs.as_mut() {
+            popup.refresh(outputs);
+        }
     }
     match model.screen {
         Screen::Library => updaters::update_library(model)?,
         Screen::Queue => updaters::update_queue(model)?,
+        Screen::Playlists => updaters::update_playlists(model)?,
+        Screen::Files => updaters::update_files(model)?,
+        Screen::TrackSearch => updaters::update_track_search(model)?,
     }
     Ok(())
 }
@@ -140,10 +306,31 @@ pub fn update_screens(model: &mut Model, mut update: Update) -> Result<()> {
 fn parse_msg(
     key: event::KeyEvent,
     state: &mut Vec<KeyEvent>,
-    keybinds: &KeybindMap,
+    count: &mut Option<usize>,
+    keybinds: &Keybindings,
+    context: KeyContext,
 ) -> Option<Message> {
+    let pending = !state.is_empty() || count.is_some();
+    // escape cancels a pending key sequence or count
+    if pending && key.code == KeyCode::Esc && key.modifiers.is_empty() {
+        state.clear();
+        *count = None;
+        return None;
+    }
+    // digits start a count unless they are bound, like the screen keys
+    if let KeyCode::Char(c @ '0'..='9') = key.code {
+        if state.is_empty()
+            && key.modifiers.is_empty()
+            && (count.is_some()
+                || (c != '0' && keybinds.lookup(context, &[key]).is_none()))
+        {
+            let digit = c.to_digit(10).unwrap_or(0) as usize;
+            *count = Some((count.unwrap_or(0) * 10 + digit).min(MAX_COUNT));
+            return None;
+        }
+    }
     state.push(key);
-    match keybinds.lookup(state) {
+    match keybinds.lookup(context, state) {
         Some(KeybindTarget::Msg(m)) => {
             state.clear();
             Some(m.clone())
@@ -158,20 +345,51 @@ fn parse_msg(
 
 pub fn handle_key(model: &mut Model, k: KeyEvent) -> Result<Update> {
     match model.state {
-        State::Searching => match model.screen {
-            Screen::Library => {
-                Ok(handlers::library_handler::handle_search(model, k)?)
+        State::Searching => {
+            let bound =
+                model.config.keybindings.lookup(KeyContext::Search, &[k]);
+            let k = match bound {
+                Some(KeybindTarget::Msg(m)) => SearchKey::Bound(m.clone()),
+                _ => SearchKey::Typed(k),
+            };
+            if model.help.is_some() {
+                return handlers::help_handler::handle_search(model, k);
             }
-            Screen::Queue => {
-                Ok(handlers::queue_handler::handle_search(model, k)?)
+            match model.screen {
+                Screen::Library => {
+                    Ok(handlers::library_handler::handle_search(model, k)?)
+                }
+                Screen::Queue => {
+                    Ok(handlers::queue_handler::handle_search(model, k)?)
+                }
+                Screen::Playlists => {
+                    Ok(handlers::playlist_handler::handle_search(model, k)?)
+                }
+                Screen::Files => {
+                    Ok(handlers::files_handler::handle_search(model, k)?)
+                }
+                Screen::TrackSearch => {
+                    Ok(handlers::track_search_handler::handle_search(model, k)?)
+                }
             }
-        },
+        }
+        State::Prompting => {
+            Ok(handlers::prompt_handler::handle_prompt(model, k)?)
+        }
         State::Running => {
-            if let Some(m) =
-                parse_msg(k, &mut model.parse_state, &model.config.keybindings)
-            {
-                Ok(handle_msg(model, m)?)
+            let context = model.key_context();
+            if let Some(m) = parse_msg(
+                k,
+                &mut model.parse_state,
+                &mut model.parse_count,
+                &model.config.keybindings,
+                context,
+            ) {
+                let update = handle_counted(model, m);
+                model.parse_count = None;
+                update
             } else {
+                model.parse_state_at = Instant::now();
                 Ok(Update::empty())
             }
         }
@@ -179,6 +397,28 @@ pub fn handle_key(model: &mut Model, k: KeyEvent) -> Result<Update> {
     }
 }
 
+// motions, seeking, volume and queue deletes use the count themselves
+fn handle_counted(model: &mut Model, m: Message) -> Result<Update> {
+    let repeat = matches!(
+        m,
+        Message::Direction(Dirs::Horiz(_))
+            | Message::MoveUp
+            | Message::MoveDown
+            | Message::NextSong
+            | Message::PreviousSong
+            | Message::SelectAndNext
+            | Message::ToggleMark
+    );
+    if !repeat {
+        return handle_msg(model, m);
+    }
+    for _ in 1..model.count() {
+        let update = handle_msg(model, m.clone())?;
+        update_screens(model, update)?;
+    }
+    handle_msg(model, m)
+}
+
 pub fn handle_msg(model: &mut Model, m: Message) -> Result<Update> {
     match m {
         Message::SwitchState(state) => {
@@ -193,26 +433,24 @@ pub fn handle_msg(model: &mut Model, m: Message) -> Result<Update> {
             Ok(Update::empty())
         }
         Message::UpdateDB => {
-            let id = model.conn.update()?;
-            loop {
-                // hang until finished updating (this update job)
-                if model
-                    .conn
-                    .status()
-                    .ok()
-                    .and_then(|i| i.updating_db)
-                    .is_none_or(|i| i != id)
-                {
-                    break;
-                }
-            }
-            build_library::build_library(model)?;
+            // the library is rebuilt on the database idle event
+            model.worker.request(Request::UpdateDB);
+            Ok(Update::empty())
+        }
+        Message::NextLibraryView => {
+            let next =
+                (model.library.view + 1) % model.config.library_views.len();
+            handlers::library_handler::switch_view(model, next);
             Ok(Update::empty())
         }
         Message::PlayPause => {
             model.conn.toggle_pause()?;
             Ok(Update::STATUS)
         }
+        Message::Play => {
+            model.conn.play()?;
+            Ok(Update::CURRENT_SONG | Update::STATUS)
+        }
         Message::NextSong => match model.status.state {
             PlayState::Stop => Ok(Update::empty()),
             _ => {
@@ -233,7 +471,8 @@ pub fn handle_msg(model: &mut Model, m: Message) -> Result<Update> {
             {
                 let delta = Duration::from_secs(
                     model.config.seek_seconds.unsigned_abs(),
-                );
+                )
+                .saturating_mul(model.count() as u32);
 
                 let new_pos = if direction == SeekDirection::Backward {
                     (current_pos.checked_sub(delta))
@@ -262,10 +501,59 @@ pub fn handle_msg(model: &mut Model, m: Message) -> Result<Update> {
             }?;
             Ok(Update::STATUS)
         }
+        Message::VolumeUp | Message::VolumeDown => {
+            if model.status.volume < 0 {
+                // mpd has no mixer for the current output
+                return Ok(Update::empty());
+            }
+            let step = model.config.volume_step * model.count() as i64;
+            let delta = if matches!(m, Message::VolumeUp) {
+                step
+            } else {
+                -step
+            };
+            let volume = (model.status.volume as i64 + delta).clamp(0, 100);
+            model.conn.volume(volume as i8)?;
+            Ok(Update::STATUS)
+        }
+        Message::SetVolume => {
+            model.prompt = Some(Prompt::new(PromptKind::SetVolume));
+            model.state = State::Prompting;
+            Ok(Update::empty())
+        }
+        Message::CommandLine => {
+            model.prompt = Some(Prompt::new(PromptKind::Command {
+                completions: Vec::new(),
+            }));
+            model.state = State::Prompting;
+            Ok(Update::empty())
+        }
+        Message::Outputs => {
+            model.outputs = match model.outputs {
+                Some(_) => None,
+                None => Some(OutputsPopup::new(model.conn.outputs()?)),
+            };
+            Ok(Update::empty())
+        }
+        other if model.outputs.is_some() => {
+            handlers::outputs_handler::handle_outputs(model, other)
+        }
+        Message::Help if model.help.is_none() => {
+            model.help = Some(HelpPopup::new(&model.config.keybindings));
+            Ok(Update::empty())
+        }
+        other if model.help.is_some() => {
+            handlers::help_handler::handle_help(model, other)
+        }
         Message::Clear => {
             model.conn.clear()?;
             Ok(Update::STATUS | Update::QUEUE | Update::CURRENT_SONG)
         }
+        Message::SaveQueue => {
+            model.prompt = Some(Prompt::new(PromptKind::SaveQueue));
+            model.state = State::Prompting;
+            Ok(Update::empty())
+        }
         other => match model.screen {
             Screen::Library => {
                 handlers::library_handler::handle_library(model, other)
@@ -273,6 +561,75 @@ pub fn handle_msg(model: &mut Model, m: Message) -> Result<Update> {
             Screen::Queue => {
                 handlers::queue_handler::handle_queue(model, other)
             }
+            Screen::Playlists => {
+                handlers::playlist_handler::handle_playlists(model, other)
+            }
+            Screen::Files => {
+                handlers::files_handler::handle_files(model, other)
+            }
+            Screen::TrackSearch => {
+                handlers::track_search_handler::handle_track_search(
+                    model, other,
+                )
+            }
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    fn parse_keys(
+        keys: &str,
+        keybinds: &Keybindings,
+    ) -> (Option<Message>, Option<usize>) {
+        let mut state = Vec::new();
+        let mut count = None;
+        let mut msg = None;
+        for c in keys.chars() {
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::empty());
+            msg = parse_msg(
+                key,
+                &mut state,
+                &mut count,
+                keybinds,
+                KeyContext::Queue,
+            );
+        }
+        (msg, count)
+    }
+
+    #[test]
+    fn bound_digits_run_their_command() {
+        let keybinds = Keybindings::default().with_qwerty_style();
+        let (msg, count) = parse_keys("1", &keybinds);
+        assert_eq!(msg, Some(Message::SwitchScreen(1)));
+        assert_eq!(count, None);
+    }
+
+    #[test]
+    fn unbound_digits_start_a_count() {
+        let keybinds = Keybindings::default().with_qwerty_style();
+        let (msg, count) = parse_keys("50j", &keybinds);
+        assert_eq!(msg, Some(Message::Direction(Dirs::Vert(Vertical::Down))));
+        assert_eq!(count, Some(50));
+    }
+
+    #[test]
+    fn started_count_takes_bound_digits() {
+        let keybinds = Keybindings::default().with_qwerty_style();
+        let (msg, count) = parse_keys("512j", &keybinds);
+        assert_eq!(msg, Some(Message::Direction(Dirs::Vert(Vertical::Down))));
+        assert_eq!(count, Some(512));
+    }
+
+    #[test]
+    fn leading_zero_is_not_a_count() {
+        let keybinds = Keybindings::default().with_qwerty_style();
+        let (msg, count) = parse_keys("0", &keybinds);
+        assert!(msg.is_none());
+        assert_eq!(count, None);
+    }
+}
@@ -0,0 +1,49 @@
+use crate::event_handler::Result;
+use crate::model::proto::*;
+use crate::model::{FileEntry, Model};
+use mpd::Song;
+
+/// Lists the current directory of the file browser. Directories come from
+/// `listfiles`, since `lsinfo` replies can't be split into directories and
+/// songs by the mpd client; songs come from `lsinfo` so that they carry tags.
+pub fn build_directory(model: &mut Model) -> Result<()> {
+    let dir = model.files.current_dir();
+    let mut dirs: Vec<String> = model
+        .conn
+        .listfiles(&dir)?
+        .into_iter()
+        .filter(|(key, _)| key == "directory")
+        .map(|(_, name)| {
+            if dir.is_empty() {
+                name
+            } else {
+                format!("{}/{}", dir, name)
+            }
+        })
+        .collect();
+    dirs.sort_by_key(|d| d.to_lowercase());
+    let songs = model.conn.lsinfo(path_song(&dir))?;
+
+    model.files.contents = dirs
+        .into_iter()
+        .map(FileEntry::Directory)
+        .chain(
+            songs
+                .into_iter()
+                .filter(|s| !s.file.is_empty())
+                .map(FileEntry::Song),
+        )
+        .collect();
+    model.files.fetched = true;
+    model.files.watch_oob();
+    Ok(())
+}
+
+/// A placeholder song whose only purpose is to carry a path to commands
+/// taking a song path.
+pub fn path_song(path: &str) -> Song {
+    Song {
+        file: path.to_string(),
+        ..Default::default()
+    }
+}
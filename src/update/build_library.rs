@@ -1,21 +1,27 @@
 extern crate mpd;
-use crate::event_handler::Result;
+use crate::config::AlbumOrder;
 use crate::model::proto::*;
 use crate::model::{AlbumData, ArtistData, Model};
+use crate::util::song_tag;
+use crate::worker::Request;
 use itertools::Itertools;
-use mpd::{Query, Term};
+use mpd::{Query, Song, Term};
 use std::borrow::Cow::Borrowed;
+use std::cmp::Ordering;
 
-pub fn build_library(model: &mut Model) -> Result<()> {
-    let artists = model
-        .conn
-        .list_group_2(("albumartistsort".into(), "albumartist".into()))?;
-
+/// Fills the library from a `list <sort tag> group <tag>` response, or a
+/// plain `list <tag>` for tags without sort names. Every value of the tag
+/// is followed by its sort names.
+pub fn build_library(
+    model: &mut Model,
+    tag: &str,
+    entries: Vec<(String, String)>,
+) {
     model.library.contents.clear();
-    for chunk in artists.chunk_by(|_a, b| b.0 == "AlbumArtistSort") {
-        if let Some(albumartist) = chunk.first().map(|i| i.1.clone()) {
+    for chunk in entries.chunk_by(|_a, b| !b.0.eq_ignore_ascii_case(tag)) {
+        if let Some(name) = chunk.first().map(|i| i.1.clone()) {
             model.library.contents.push(ArtistData::from_names(
-                albumartist,
+                name,
                 chunk.iter().skip(1).map(|i| i.1.clone()).collect(),
             ));
         }
@@ -23,59 +29,125 @@ pub fn build_library(model: &mut Model) -> Result<()> {
     // sort by sort name
     model.library.contents.sort_by(|a, b| {
         let a_name = a.sort_names.first().unwrap_or(&a.name);
-        let b_name = b.sort_names.first().unwrap_or(&a.name);
+        let b_name = b.sort_names.first().unwrap_or(&b.name);
         a_name.to_lowercase().cmp(&b_name.to_lowercase())
     });
     model.library.contents.shrink_to_fit();
-    Ok(())
 }
 
-pub fn add_tracks(model: &mut Model) -> Result<()> {
-    let song_data = model.conn.find(
-        Query::new().and(
-            Term::Tag(Borrowed("AlbumArtist")),
-            match model.library.selected_item_mut() {
-                Some(a) => a.name.clone(),
-                None => return Ok(()),
-            },
-        ),
-        None,
-    )?;
+/// The values of the tags the track panel groups songs by.
+fn group_values(song: &Song, tags: &[String]) -> Vec<String> {
+    tags.iter()
+        .map(|t| song_tag(song, t).cloned().unwrap_or_default())
+        .collect()
+}
+
+/// The header of a group in the track panel.
+fn group_name(tags: &[String], values: &[String]) -> String {
+    tags.iter()
+        .zip(values)
+        .map(|(tag, value)| match value.as_str() {
+            "" => format!("<{} NOT FOUND>", tag.to_uppercase()),
+            value => value.to_string(),
+        })
+        .join(" / ")
+}
+
+/// The query for the songs of an entry of the artist panel, or of one of
+/// its groups when given their values.
+pub fn library_query<'a>(
+    tags: &'a [String],
+    name: String,
+    values: Vec<String>,
+) -> Query<'a> {
+    let mut query = Query::new();
+    query.and(Term::Tag(Borrowed(&tags[0])), name);
+    for (tag, value) in tags[1..].iter().zip(values) {
+        query.and(Term::Tag(Borrowed(tag)), value);
+    }
+    query
+}
+
+/// Dates the albums and orders them, undated albums last when ordering by
+/// date.
+fn sort_albums(albums: &mut [AlbumData], order: AlbumOrder) {
+    for album in albums.iter_mut() {
+        album.date = order.date_tags().iter().find_map(|tag| {
+            album.tracks.iter().find_map(|s| song_tag(s, tag)).cloned()
+        });
+    }
+    let by_name = |a: &AlbumData, b: &AlbumData| {
+        a.name.to_lowercase().cmp(&b.name.to_lowercase())
+    };
+    match order {
+        AlbumOrder::Name => albums.sort_by(by_name),
+        AlbumOrder::OriginalDate | AlbumOrder::Date => {
+            albums.sort_by(|a, b| {
+                match (&a.date, &b.date) {
+                    (Some(x), Some(y)) => x.cmp(y),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+                .then_with(|| by_name(a, b))
+            })
+        }
+    }
+}
+
+// one entry at a time; the updater asks again once it returns
+pub fn add_tracks(model: &mut Model) {
+    let Some(item) = model.library.selected_item() else {
+        return;
+    };
+    let busy = model
+        .worker
+        .pending
+        .iter()
+        .any(|r| matches!(r, Request::Tracks(..)));
+    if !item.fetched && !busy {
+        let name = item.name.clone();
+        let tags = model.library_view().tags.clone();
+        model.worker.request(Request::Tracks(tags, name));
+    }
+}
+
+pub fn set_tracks(
+    model: &mut Model,
+    tags: &[String],
+    name: &str,
+    songs: Vec<Song>,
+) {
+    // the view may have changed while the worker was busy
+    if tags != model.library_view().tags.as_slice() {
+        return;
+    }
     let mut albums: Vec<AlbumData> = Vec::new();
 
-    // chunks have album field invariant!
-    for album in song_data.chunk_by(|a, b| {
-        a.tags.iter().find(|t| t.0 == "Album")
-            == b.tags.iter().find(|t| t.0 == "Album")
-    }) {
-        if let Some(track) = album.first() {
-            albums.push(AlbumData {
-                name: track
-                    .tags
-                    .iter()
-                    .find(|t| t.0 == "Album")
-                    .cloned()
-                    .map(|i| i.1)
-                    .unwrap_or("<ALBUM NOT FOUND>".into()),
-                tracks: album.to_vec(),
+    // mpd returns songs in database order, which may split up an album
+    for song in songs {
+        let values = group_values(&song, &tags[1..]);
+        match albums.iter_mut().find(|a| a.values == values) {
+            Some(album) => album.tracks.push(song),
+            None => albums.push(AlbumData {
+                name: group_name(&tags[1..], &values),
+                values,
+                date: None,
+                tracks: vec![song],
                 expanded: true,
-            });
+            }),
         }
     }
-    if let Some(states) = model
-        .library
-        .selected_item()
-        .map(|item| item.albums.iter().map(|i| i.expanded).collect_vec())
-    {
-        if states.len() == albums.len() {
-            for (i, prev) in albums.iter_mut().zip(states) {
-                i.expanded = prev;
-            }
+    sort_albums(&mut albums, model.config.album_order);
+    let Some(item) = model.library.contents.iter_mut().find(|a| a.name == name)
+    else {
+        return;
+    };
+    if item.albums.len() == albums.len() {
+        for (album, prev) in albums.iter_mut().zip(&item.albums) {
+            album.expanded = prev.expanded;
         }
     }
-    if let Some(item) = model.library.selected_item_mut() {
-        item.albums = albums;
-        item.fetched = true;
-    }
-    Ok(())
+    item.albums = albums;
+    item.fetched = true;
 }
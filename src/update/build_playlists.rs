@@ -0,0 +1,35 @@
+use crate::event_handler::Result;
+use crate::model::proto::*;
+use crate::model::{FilterCache, Model, PlaylistData};
+
+pub fn build_playlists(model: &mut Model) -> Result<()> {
+    let selected = model.playlists.selected_name();
+    let mut playlists = model.conn.playlists()?;
+    playlists.sort_by_key(|p| p.name.to_lowercase());
+
+    model.playlists.contents = playlists
+        .into_iter()
+        .map(|p| PlaylistData::new(p.name))
+        .collect();
+    model.playlists.search.cache = FilterCache::new();
+    model.playlists.fetched = true;
+    model.playlists.set_selected(None);
+    if let Some(name) = selected {
+        model.playlists.select_by_name(&name);
+    }
+    Ok(())
+}
+
+pub fn add_playlist_tracks(model: &mut Model) -> Result<()> {
+    let name = match model.playlists.selected_name() {
+        Some(name) => name,
+        None => return Ok(()),
+    };
+    let tracks = model.conn.playlist(&name)?;
+    if let Some(playlist) = model.playlists.selected_item_mut() {
+        playlist.tracks = tracks;
+        playlist.fetched = true;
+        playlist.watch_oob();
+    }
+    Ok(())
+}
@@ -6,10 +6,22 @@ use event::KeyModifiers;
 use nucleo_matcher::Matcher;
 use proto::*;
 
+pub mod command_handler;
+pub mod files_handler;
+pub mod help_handler;
 pub mod library_handler;
+pub mod outputs_handler;
+pub mod playlist_handler;
+pub mod prompt_handler;
 pub mod queue_handler;
+pub mod track_search_handler;
 
-pub fn handle_vertical(msg: Vertical, selector: &mut impl Selector) {
+/// Moves the selection `count` rows up or down, or to the top or bottom.
+pub fn handle_vertical(
+    msg: Vertical,
+    count: usize,
+    selector: &mut impl Selector,
+) {
     match selector.selected() {
         None => {
             if selector.len() != 0 {
@@ -17,8 +29,8 @@ pub fn handle_vertical(msg: Vertical, selector: &mut impl Selector) {
             }
         }
         Some(sel) => selector.set_selected(match msg {
-            Vertical::Up => Some(safe_subtract(sel, 1, selector.len())),
-            Vertical::Down => Some(safe_add(sel, 1, selector.len())),
+            Vertical::Up => Some(safe_subtract(sel, count, selector.len())),
+            Vertical::Down => Some(safe_add(sel, count, selector.len())),
             Vertical::Top => Some(0),
             Vertical::Bottom => {
                 Some(safe_subtract(selector.len(), 1, selector.len()))
@@ -27,76 +39,138 @@ pub fn handle_vertical(msg: Vertical, selector: &mut impl Selector) {
     }
 }
 
+/// Reports how many songs were added to a queue that was `before` long,
+/// once the status has been refreshed.
+pub fn notify_added(model: &mut Model, before: u32) {
+    model.added_from = Some(before);
+}
+
+/// Inserts songs right after the current song, in order, or appends them if
+/// nothing is playing. With `play`, starts playing the first of them.
+pub fn insert_next(model: &mut Model, songs: Vec<Songs>, play: bool) {
+    if !songs.is_empty() {
+        model.worker.request(Request::InsertNext(songs, play));
+    }
+}
+
 pub fn scroll_screenful(
     dir: Vertical,
     height: usize,
+    count: usize,
     selector: &mut impl Selector,
 ) {
     let len = selector.len();
-    match dir {
-        Vertical::Up => {
-            selector.set_selected(Some(selector.offset()));
-            selector.set_offset(safe_subtract(selector.offset(), height, len));
-        }
-        Vertical::Down => {
-            let mut next = safe_add(selector.offset(), height, len);
-            if next > 0 && next < safe_subtract(len, 1, len) {
-                next = safe_subtract(next, 3, len);
-                selector.set_offset(next);
+    for _ in 0..count {
+        match dir {
+            Vertical::Up => {
+                selector.set_selected(Some(selector.offset()));
+                selector.set_offset(safe_subtract(
+                    selector.offset(),
+                    height,
+                    len,
+                ));
+            }
+            Vertical::Down => {
+                let mut next = safe_add(selector.offset(), height, len);
+                if next > 0 && next < safe_subtract(len, 1, len) {
+                    next = safe_subtract(next, 3, len);
+                    selector.set_offset(next);
+                }
+                selector.set_selected(Some(next));
             }
-            selector.set_selected(Some(next));
+            _ => {}
+        }
+    }
+}
+
+/// A key typed while searching: either bound in the search context, or
+/// input for the query.
+pub enum SearchKey {
+    Bound(Message),
+    Typed(KeyEvent),
+}
+
+/// Edits a search query with a typed key.
+fn edit_query(query: &mut String, k: KeyEvent) {
+    if k.modifiers.contains(KeyModifiers::CONTROL) {
+        // TODO: keep track of cursor and implement AEFB
+        if k.code == KeyCode::Char('u') {
+            query.clear();
+        }
+        return;
+    }
+    match k.code {
+        KeyCode::Char(c) => query.push(c),
+        KeyCode::Backspace => {
+            let _ = query.pop();
         }
         _ => {}
     }
 }
 
+/// Recalls older or newer queries, and records the query once it is
+/// submitted. Returns whether the key was handled here.
+fn handle_history(
+    history: Option<&mut SearchHistory>,
+    query: &mut String,
+    k: &SearchKey,
+) -> bool {
+    match (history, k) {
+        (Some(history), SearchKey::Bound(Message::History(d))) => {
+            history.recall(query, d);
+            true
+        }
+        (None, SearchKey::Bound(Message::History(_))) => true,
+        (
+            Some(history),
+            SearchKey::Bound(Message::Select | Message::SelectAndNext),
+        ) => {
+            history.push(query);
+            false
+        }
+        _ => false,
+    }
+}
+
 // TODO: Figure out a way to eliminate code duplication here
 pub fn handle_search_k_tracksel(
     artist: &mut ArtistData,
-    k: KeyEvent,
+    k: SearchKey,
     matcher: &mut Matcher,
+    history: Option<&mut SearchHistory>,
 ) -> Option<Message> {
-    if k.modifiers.contains(KeyModifiers::CONTROL) {
-        match k.code {
-            // TODO: keep track of cursor and implement AEFB
-            KeyCode::Char('u') => artist.search.query.clear(),
-            KeyCode::Char('n') => {
-                if let Some(Some(r)) = artist.selected_item().map(|i| i.rank) {
-                    let idx = artist
-                        .contents()
-                        .iter()
-                        .position(|i| i.rank == Some(r + 1));
-                    if idx.is_some() {
-                        artist.set_selected(idx)
-                    }
+    let handled = handle_history(history, &mut artist.search.query, &k);
+    match k {
+        _ if handled => {}
+        SearchKey::Bound(Message::Direction(Dirs::Vert(Vertical::Down))) => {
+            if let Some(Some(r)) = artist.selected_item().map(|i| i.rank) {
+                let idx = artist
+                    .contents()
+                    .iter()
+                    .position(|i| i.rank == Some(r + 1));
+                if idx.is_some() {
+                    artist.set_selected(idx)
                 }
             }
-            KeyCode::Char('p') => {
-                if let Some(Some(r)) = artist.selected_item().map(|i| i.rank) {
-                    if r > 0 {
-                        artist.set_selected(
-                            artist
-                                .contents()
-                                .iter()
-                                .position(|i| i.rank == Some(r - 1)),
-                        );
-                    }
+        }
+        SearchKey::Bound(Message::Direction(Dirs::Vert(Vertical::Up))) => {
+            if let Some(Some(r)) = artist.selected_item().map(|i| i.rank) {
+                if r > 0 {
+                    artist.set_selected(
+                        artist
+                            .contents()
+                            .iter()
+                            .position(|i| i.rank == Some(r - 1)),
+                    );
                 }
             }
-            _ => {}
         }
-    } else {
-        match k.code {
-            KeyCode::Char(c) => artist.search.query.push(c),
-            KeyCode::Backspace => {
-                let _ = artist.search.query.pop();
-            }
-            KeyCode::Esc => {
-                return Some(Message::LocalSearch(SearchMsg::End));
-            }
-            KeyCode::Enter => return Some(Message::Select),
-            _ => {}
+        SearchKey::Bound(Message::Direction(_)) => {}
+        SearchKey::Bound(Message::Escape) => {
+            return Some(Message::LocalSearch(SearchMsg::End));
         }
+        SearchKey::Bound(m) => return Some(m),
+        SearchKey::Typed(k) => edit_query(&mut artist.search.query, k),
     }
     artist.update_search(matcher);
     None
@@ -104,35 +178,22 @@ pub fn handle_search_k_tracksel(
 
 pub fn handle_search_k<T>(
     s: &mut impl Searchable<T>,
-    k: KeyEvent,
+    k: SearchKey,
     matcher: &mut Matcher,
     top_k: usize,
+    history: Option<&mut SearchHistory>,
 ) -> Option<Message> {
-    if k.modifiers.contains(KeyModifiers::CONTROL) {
-        match k.code {
-            // TODO: keep track of cursor and implement AEFB
-            KeyCode::Char('u') => s.filter_mut().query.clear(),
-            KeyCode::Char('n') => handle_vertical(Vertical::Down, s),
-            KeyCode::Char('p') => handle_vertical(Vertical::Up, s),
-            _ => {}
+    let handled = handle_history(history, &mut s.filter_mut().query, &k);
+    match k {
+        _ if handled => {}
+        SearchKey::Bound(Message::Direction(Dirs::Vert(d))) => {
+            handle_vertical(d, 1, s)
         }
-    } else {
-        match k.code {
-            KeyCode::Char(c) => {
-                s.filter_mut().query.push(c);
-            }
-            KeyCode::Backspace => {
-                let _ = s.filter_mut().query.pop();
-            }
-            KeyCode::Tab => {
-                return Some(Message::ToggleScreen);
-            }
-            KeyCode::Esc => {
-                return Some(Message::LocalSearch(SearchMsg::End));
-            }
-            KeyCode::Enter => return Some(Message::Select),
-            _ => {}
+        SearchKey::Bound(Message::Escape) => {
+            return Some(Message::LocalSearch(SearchMsg::End));
         }
+        SearchKey::Bound(m) => return Some(m),
+        SearchKey::Typed(k) => edit_query(&mut s.filter_mut().query, k),
     }
     s.update_filter_cache(matcher, Some(top_k));
     s.watch_oob();
@@ -0,0 +1,244 @@
+use super::*;
+use crate::config::keybind::{get_message, MESSAGES};
+use crate::event_handler::Result;
+use mpd::Query;
+use mpd::Term;
+use std::borrow::Cow::Borrowed;
+use std::time::Duration;
+
+/// Commands that take an argument, on top of the names in `MESSAGES`.
+const ARG_COMMANDS: [&str; 5] = ["add", "save", "seek", "view", "volume"];
+/// The kinds of `add`, and the tag each one searches.
+const ADD_KINDS: [(&str, &str); 3] = [
+    ("album", "Album"),
+    ("artist", "AlbumArtist"),
+    ("title", "Title"),
+];
+
+/// Runs a line typed at the command line. Mistakes are reported as
+/// notifications rather than errors.
+pub fn run_command(model: &mut Model, input: &str) -> Result<Update> {
+    let (name, arg) = match input.split_once(' ') {
+        Some((name, arg)) => (name, arg.trim()),
+        None => (input, ""),
+    };
+    match (name, arg) {
+        ("", _) => Ok(Update::empty()),
+        ("seek", arg) if !arg.is_empty() => seek_to(model, arg),
+        ("volume", arg) if !arg.is_empty() => {
+            let update = prompt_handler::set_volume(model, arg)?;
+            if update.is_empty() {
+                model.notify_error(format!("volume: invalid value {}", arg));
+            }
+            Ok(update)
+        }
+        ("save", arg) if !arg.is_empty() => {
+            queue_handler::save_queue(model, arg, false)
+        }
+        ("add", arg) if !arg.is_empty() => add(model, arg),
+        ("view", arg) if !arg.is_empty() => view(model, arg),
+        (name, "") if ARG_COMMANDS.contains(&name) && name != "seek" => {
+            fail(model, format!("{}: missing argument", name))
+        }
+        (name, arg) => match get_message(name) {
+            Some(_) if !arg.is_empty() => {
+                fail(model, format!("{}: takes no argument", name))
+            }
+            Some(m) => handle_msg(model, m),
+            None => fail(model, format!("unknown command: {}", name)),
+        },
+    }
+}
+
+fn fail(model: &mut Model, text: String) -> Result<Update> {
+    model.notify_error(text);
+    Ok(Update::empty())
+}
+
+/// Seeks to a time like `90`, `1:30` or `1:02:03`, or by it when prefixed
+/// with `+` or `-`.
+fn seek_to(model: &mut Model, arg: &str) -> Result<Update> {
+    let (sign, time) = match arg.strip_prefix(['+', '-']) {
+        Some(time) => (arg.chars().next(), time),
+        None => (None, arg),
+    };
+    let Some(offset) = parse_time(time) else {
+        return fail(model, format!("seek: invalid time {}", arg));
+    };
+    let (Some(song), Some(elapsed)) = (model.status.song, model.status.elapsed)
+    else {
+        return fail(model, "seek: nothing is playing".into());
+    };
+    let mut target = match sign {
+        Some('+') => elapsed + offset,
+        Some(_) => elapsed.saturating_sub(offset),
+        None => offset,
+    };
+    if let Some(duration) = model.status.duration {
+        target = target.min(duration);
+    }
+    model.conn.seek(song.pos, target)?;
+    Ok(Update::STATUS)
+}
+
+fn parse_time(s: &str) -> Option<Duration> {
+    if s.split(':').count() > 3 {
+        return None;
+    }
+    let mut secs: u64 = 0;
+    for part in s.split(':') {
+        secs = secs.checked_mul(60)?.checked_add(part.parse().ok()?)?;
+    }
+    Some(Duration::from_secs(secs))
+}
+
+fn add(model: &mut Model, arg: &str) -> Result<Update> {
+    let (kind, name) = arg.split_once(' ').unwrap_or((arg, ""));
+    let Some((_, tag)) = ADD_KINDS.iter().find(|(k, _)| *k == kind) else {
+        return fail(model, "add: expected album, artist or title".into());
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        return fail(model, format!("add {}: missing name", kind));
+    }
+    let before = model.status.queue_len;
+    model
+        .conn
+        .findadd(Query::new().and(Term::Tag(Borrowed(tag)), name))?;
+    notify_added(model, before);
+    Ok(Update::STATUS | Update::QUEUE | Update::START_PLAYING)
+}
+
+fn view(model: &mut Model, name: &str) -> Result<Update> {
+    let views = &model.config.library_views;
+    let Some(view) = views.iter().position(|v| v.name == name) else {
+        return fail(model, format!("view: no library view named {}", name));
+    };
+    library_handler::switch_view(model, view);
+    Ok(Update::empty())
+}
+
+/// Completes the last word of the command line. A single candidate is
+/// filled in. Otherwise the input is extended to the candidates' common
+/// prefix and the candidates are listed.
+pub fn complete(model: &mut Model) -> Result<()> {
+    let input = match &model.prompt {
+        Some(Prompt {
+            kind: PromptKind::Command { .. },
+            input,
+        }) => input.clone(),
+        _ => return Ok(()),
+    };
+    let (done, word, candidates) = match input.split_once(' ') {
+        None => (String::new(), input.as_str(), command_names()),
+        Some(("add", rest)) => match rest.split_once(' ') {
+            None => (
+                "add ".to_string(),
+                rest,
+                ADD_KINDS.iter().map(|(k, _)| k.to_string()).collect(),
+            ),
+            Some((kind, name)) => {
+                (format!("add {} ", kind), name, add_candidates(model, kind))
+            }
+        },
+        Some(("view", name)) => (
+            "view ".to_string(),
+            name,
+            model
+                .config
+                .library_views
+                .iter()
+                .map(|v| v.name.clone())
+                .collect(),
+        ),
+        Some(("save", name)) => (
+            "save ".to_string(),
+            name,
+            model
+                .conn
+                .playlists()?
+                .into_iter()
+                .map(|p| p.name)
+                .collect(),
+        ),
+        Some(_) => return Ok(()),
+    };
+    let mut matches: Vec<String> = candidates
+        .into_iter()
+        .filter(|c| c.starts_with(word))
+        .collect();
+    matches.sort();
+    matches.dedup();
+
+    let Some(prompt) = model.prompt.as_mut() else {
+        return Ok(());
+    };
+    let completions = match matches.as_slice() {
+        [] => Vec::new(),
+        [only] => {
+            // commands and kinds of add are followed by an argument
+            let more = match done.as_str() {
+                "" => ARG_COMMANDS.contains(&only.as_str()),
+                d => d == "add ",
+            };
+            prompt.input = done + only;
+            if more {
+                prompt.input.push(' ');
+            }
+            Vec::new()
+        }
+        _ => {
+            prompt.input = done + &common_prefix(&matches);
+            matches
+        }
+    };
+    prompt.kind = PromptKind::Command { completions };
+    Ok(())
+}
+
+fn command_names() -> Vec<String> {
+    MESSAGES
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .chain(ARG_COMMANDS.iter().map(|name| name.to_string()))
+        .collect()
+}
+
+/// Names `add` can complete from the library that has been loaded so far,
+/// when the library view has the tag the kind searches.
+fn add_candidates(model: &Model, kind: &str) -> Vec<String> {
+    let artists = model.library.contents.iter();
+    let Some((_, tag)) = ADD_KINDS.iter().find(|(k, _)| *k == kind) else {
+        return Vec::new();
+    };
+    let position = model
+        .library_view()
+        .tags
+        .iter()
+        .position(|t| t.eq_ignore_ascii_case(tag));
+    match (kind, position) {
+        ("title", _) => artists
+            .flat_map(|a| a.albums.iter())
+            .flat_map(|album| album.tracks.iter())
+            .filter_map(|song| song.title.clone())
+            .collect(),
+        (_, Some(0)) => artists.map(|a| a.name.clone()).collect(),
+        (_, Some(i)) => artists
+            .flat_map(|a| a.albums.iter())
+            .filter_map(|album| album.values.get(i - 1).cloned())
+            .collect(),
+        (_, None) => Vec::new(),
+    }
+}
+
+fn common_prefix(words: &[String]) -> String {
+    let first = &words[0];
+    let len = words.iter().skip(1).fold(first.len(), |len, word| {
+        first[..len]
+            .char_indices()
+            .zip(word.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(word.len()), |((i, _), _)| i)
+    });
+    first[..len].to_string()
+}
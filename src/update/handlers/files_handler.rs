@@ -0,0 +1,100 @@
+use super::*;
+use crate::event_handler::Result;
+use crate::view::layout::files_layout::FilesLayout;
+use crate::view::layout::InoriLayout;
+use mpd::{Query, Term};
+
+pub fn handle_files(model: &mut Model, msg: Message) -> Result<Update> {
+    match msg {
+        Message::LocalSearch(SearchMsg::Start) => {
+            model.files.search.set_on();
+            if model.files.len() != 0 {
+                model.files.set_selected(Some(0))
+            }
+            model.state = State::Searching;
+            Ok(Update::empty())
+        }
+        Message::LocalSearch(SearchMsg::End) => {
+            model.state = State::Running;
+            Ok(Update::empty())
+        }
+        Message::Escape => {
+            let selected =
+                model.files.selected_item().map(|e| e.path().to_string());
+            model.files.search.set_off();
+            if let Some(path) = selected {
+                let idx =
+                    model.files.contents.iter().position(|e| e.path() == path);
+                model.files.set_selected(idx);
+            }
+            Ok(Update::empty())
+        }
+        Message::ToggleScreen => {
+            model.screen = Screen::Queue;
+            Ok(Update::empty())
+        }
+        Message::Direction(Dirs::Vert(d)) => {
+            handle_vertical(d, model.count(), &mut model.files);
+            Ok(Update::empty())
+        }
+        Message::ScrollScreenful(v) => {
+            let k = FilesLayout::new(model.frame_size, model).listing.height;
+            scroll_screenful(v, k.into(), model.count(), &mut model.files);
+            Ok(Update::empty())
+        }
+        Message::Direction(Dirs::Horiz(Horizontal::Right)) | Message::Fold => {
+            if let Some(FileEntry::Directory(dir)) = model.files.selected_item()
+            {
+                let dir = dir.clone();
+                model.files.descend(&dir);
+                model.state = State::Running;
+            }
+            Ok(Update::empty())
+        }
+        Message::Direction(Dirs::Horiz(Horizontal::Left)) => {
+            model.files.ascend();
+            model.state = State::Running;
+            Ok(Update::empty())
+        }
+        Message::Select => add_entry(model),
+        Message::SelectAndNext => {
+            let res = add_entry(model);
+            handle_vertical(Vertical::Down, 1, &mut model.files);
+            res
+        }
+        _ => Ok(Update::empty()),
+    }
+}
+
+pub fn handle_search(model: &mut Model, k: SearchKey) -> Result<Update> {
+    if let Some(m) = handle_search_k(
+        &mut model.files,
+        k,
+        &mut model.matcher,
+        model.frame_size.height.into(),
+        None,
+    ) {
+        handle_msg(model, m)
+    } else {
+        Ok(Update::empty())
+    }
+}
+
+/// Adds the selected directory (recursively) or file to the queue.
+pub fn add_entry(model: &mut Model) -> Result<Update> {
+    let before = model.status.queue_len;
+    match model.files.selected_item() {
+        Some(FileEntry::Directory(dir)) => model
+            .conn
+            .findadd(Query::new().and(Term::Base, dir.clone()))?,
+        Some(FileEntry::Song(song)) => {
+            model.conn.push(song)?;
+        }
+        None => {}
+    }
+    notify_added(model, before);
+    Ok(Update::STATUS
+        | Update::QUEUE
+        | Update::START_PLAYING
+        | Update::CURRENT_SONG)
+}
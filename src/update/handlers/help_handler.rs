@@ -0,0 +1,49 @@
+use super::*;
+use crate::event_handler::Result;
+
+pub fn handle_help(model: &mut Model, msg: Message) -> Result<Update> {
+    let count = model.count();
+    let popup = match model.help.as_mut() {
+        Some(p) => p,
+        None => return Ok(Update::empty()),
+    };
+    match msg {
+        Message::Direction(Dirs::Vert(d)) => handle_vertical(d, count, popup),
+        Message::ScrollScreenful(v) => {
+            // the popup leaves two lines free above and below
+            let height = model.frame_size.height.saturating_sub(6);
+            scroll_screenful(v, height.into(), count, popup);
+        }
+        Message::LocalSearch(SearchMsg::Start) => {
+            popup.search.set_on();
+            popup.set_selected(Some(0));
+            popup.watch_oob();
+            model.state = State::Searching;
+        }
+        Message::LocalSearch(SearchMsg::End) | Message::Select => {
+            model.state = State::Running;
+        }
+        Message::Escape if popup.search.active => {
+            popup.search.set_off();
+            popup.set_selected(Some(0));
+            popup.watch_oob();
+        }
+        Message::Escape | Message::Help | Message::ToggleScreen => {
+            model.help = None;
+            model.state = State::Running;
+        }
+        _ => {}
+    }
+    Ok(Update::empty())
+}
+
+pub fn handle_search(model: &mut Model, k: SearchKey) -> Result<Update> {
+    let height = model.frame_size.height.into();
+    let Some(popup) = model.help.as_mut() else {
+        return Ok(Update::empty());
+    };
+    match handle_search_k(popup, k, &mut model.matcher, height, None) {
+        Some(m) => handle_help(model, m),
+        None => Ok(Update::empty()),
+    }
+}
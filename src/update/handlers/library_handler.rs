@@ -1,12 +1,14 @@
 use super::*;
+use crate::config::LibraryView;
 use crate::event_handler::Result;
 use crate::model::ItemRef::*;
 use crate::model::LibActiveSelector::*;
+use crate::update::build_library::library_query;
 use crate::view::layout::library_layout::LibraryLayout;
 use crate::view::layout::InoriLayout;
+use crate::worker::Request;
 use mpd::Query;
 use mpd::Term;
-use std::borrow::Cow::Borrowed;
 
 pub fn handle_library(model: &mut Model, msg: Message) -> Result<Update> {
     match msg {
@@ -34,27 +36,53 @@ pub fn handle_library(model: &mut Model, msg: Message) -> Result<Update> {
             }
             Ok(Update::empty())
         }
+        Message::GlobalSearch(SearchMsg::Start)
+            if model.library_view().tags
+                != LibraryView::album_artists().tags =>
+        {
+            model.notify_error(
+                "global search needs a library view of albumartist and album",
+            );
+            Ok(Update::empty())
+        }
         Message::GlobalSearch(SearchMsg::Start) => {
             model.state = State::Searching;
             model.library.artist_search.set_off();
             model.library.global_search.search.set_on();
             if model.library.global_search.contents.is_none() {
-                model.update_global_search_contents()?;
+                model.worker.request(Request::GlobalSearch);
             }
             Ok(Update::empty())
         }
         Message::Escape => {
             match model.library.active {
-                ArtistSelector => model.library.artist_search.set_off(),
-                TrackSelector => {
+                ArtistSelector if model.library.artist_search.active => {
+                    model.library.artist_search.set_off()
+                }
+                TrackSelector
+                    if model
+                        .library
+                        .selected_item()
+                        .is_some_and(|a| a.search.active) =>
+                {
                     if let Some(a) = model.library.selected_item_mut() {
                         a.search.set_off();
                         a.expand_all();
                     }
                 }
+                _ => model.library.marked.clear(),
             };
             Ok(Update::empty())
         }
+        Message::Select if !model.library.marked.is_empty() => {
+            add_marked(model)
+        }
+        Message::InsertNext | Message::PlayNow => {
+            let songs = selected_songs(model);
+            model.library.marked.clear();
+            insert_next(model, songs, msg == Message::PlayNow);
+            Ok(Update::empty())
+        }
         Message::ToggleScreen => {
             model.screen = Screen::Queue;
             Ok(Update::empty())
@@ -73,7 +101,21 @@ pub fn handle_library(model: &mut Model, msg: Message) -> Result<Update> {
     }
 }
 
-pub fn handle_search(model: &mut Model, k: KeyEvent) -> Result<Update> {
+/// Switches the library to another of the configured views, and has the
+/// worker load it.
+pub fn switch_view(model: &mut Model, view: usize) {
+    model.library.view = view;
+    model.library.contents.clear();
+    model.library.marked.clear();
+    model.library.active = ArtistSelector;
+    model.library.set_selected(None);
+    model.library.artist_search.set_off();
+    model.library.artist_search.cache = FilterCache::new();
+    model.request_library();
+    model.notify(format!("library view: {}", model.library_view().name));
+}
+
+pub fn handle_search(model: &mut Model, k: SearchKey) -> Result<Update> {
     match (
         &model.library.active,
         model.library.global_search.search.active,
@@ -84,6 +126,7 @@ pub fn handle_search(model: &mut Model, k: KeyEvent) -> Result<Update> {
                 k,
                 &mut model.matcher,
                 model.frame_size.height.into(),
+                Some(&mut model.history.global),
             ) {
                 handle_msg(model, m)
             } else {
@@ -110,6 +153,7 @@ pub fn handle_search(model: &mut Model, k: KeyEvent) -> Result<Update> {
                 k,
                 &mut model.matcher,
                 model.frame_size.height.into(),
+                Some(&mut model.history.artist),
             ) {
                 handle_msg(model, m)
             } else {
@@ -118,8 +162,12 @@ pub fn handle_search(model: &mut Model, k: KeyEvent) -> Result<Update> {
         }
         (TrackSelector, _) => {
             if let Some(artist) = model.library.selected_item_mut() {
-                let msg =
-                    handle_search_k_tracksel(artist, k, &mut model.matcher);
+                let msg = handle_search_k_tracksel(
+                    artist,
+                    k,
+                    &mut model.matcher,
+                    Some(&mut model.history.tracks),
+                );
                 if let Some(m) = msg {
                     handle_msg(model, m)
                 } else {
@@ -138,14 +186,14 @@ pub fn handle_library_artist(
 ) -> Result<Update> {
     match msg {
         Message::Direction(Dirs::Vert(d)) => {
-            handle_vertical(d, &mut model.library);
+            handle_vertical(d, model.count(), &mut model.library);
             Ok(Update::CURRENT_ARTIST)
         }
         Message::ScrollScreenful(v) => {
             let k = LibraryLayout::new(model.frame_size, model)
                 .artist_select
                 .height;
-            scroll_screenful(v, k.into(), &mut model.library);
+            scroll_screenful(v, k.into(), model.count(), &mut model.library);
             Ok(Update::CURRENT_ARTIST)
         }
         Message::Direction(Dirs::Horiz(Horizontal::Right)) => {
@@ -158,7 +206,7 @@ pub fn handle_library_artist(
         Message::Select => add_artist(model),
         Message::SelectAndNext => {
             let res = add_artist(model);
-            handle_vertical(Vertical::Down, &mut model.library);
+            handle_vertical(Vertical::Down, 1, &mut model.library);
             res
         }
 
@@ -167,12 +215,16 @@ pub fn handle_library_artist(
 }
 
 pub fn add_artist(model: &mut Model) -> Result<Update> {
+    let before = model.status.queue_len;
     if let Some(artist) = model.library.selected_item() {
-        model.conn.findadd(Query::new().and(
-            Term::Tag(Borrowed("AlbumArtist")),
+        let tags = &model.config.library_views[model.library.view].tags;
+        model.conn.findadd(&library_query(
+            tags,
             artist.name.clone(),
+            Vec::new(),
         ))?;
     }
+    notify_added(model, before);
     Ok(Update::STATUS
         | Update::QUEUE
         | Update::START_PLAYING
@@ -180,33 +232,122 @@ pub fn add_artist(model: &mut Model) -> Result<Update> {
 }
 
 pub fn add_item(model: &mut Model) -> Result<Update> {
+    let before = model.status.queue_len;
+    let tags = &model.config.library_views[model.library.view].tags;
     if let Some(artist) = model.library.selected_item_mut() {
         match artist.selected_item().map(|i| i.item) {
-            Some(Album(album)) => model.conn.findadd(
-                Query::new()
-                    .and(
-                        Term::Tag(Borrowed("AlbumArtist")),
-                        artist.name.clone(),
-                    )
-                    .and(Term::Tag(Borrowed("Album")), album.name.clone()),
-            )?,
+            Some(Album(album)) => model.conn.findadd(&library_query(
+                tags,
+                artist.name.clone(),
+                album.values.clone(),
+            ))?,
             Some(Song(song)) => model
                 .conn
                 .findadd(Query::new().and(Term::File, song.file.clone()))?,
             None => {}
         }
     }
+    notify_added(model, before);
     Ok(Update::STATUS
         | Update::QUEUE
         | Update::START_PLAYING
         | Update::CURRENT_SONG)
 }
 
+pub fn add_marked(model: &mut Model) -> Result<Update> {
+    let before = model.status.queue_len;
+    let tags = &model.config.library_views[model.library.view].tags;
+    // inori-mpd has no command lists, so this is one findadd per mark. On a
+    // failure, unmark only what already reached the queue so a retry adds
+    // the rest.
+    let total = model.library.marked.len();
+    for (i, mark) in model.library.marked.clone().into_iter().enumerate() {
+        let res = match mark {
+            LibraryMark::Album { artist, album } => {
+                model.conn.findadd(&library_query(tags, artist, album))
+            }
+            LibraryMark::Song(file) => {
+                model.conn.findadd(Query::new().and(Term::File, file))
+            }
+        };
+        if let Err(e) = res {
+            model.library.marked.drain(..i);
+            let added = format!("added {} of {} marked items", i, total);
+            return match e {
+                mpd::error::Error::Server(err) => {
+                    model.notify_error(format!(
+                        "{}, then {}: {}",
+                        added, err.command, err.detail
+                    ));
+                    Ok(Update::STATUS | Update::QUEUE | Update::CURRENT_SONG)
+                }
+                e => {
+                    model.notify_error(added);
+                    Err(e.into())
+                }
+            };
+        }
+    }
+    model.library.marked.clear();
+    notify_added(model, before);
+    Ok(Update::STATUS
+        | Update::QUEUE
+        | Update::START_PLAYING
+        | Update::CURRENT_SONG)
+}
+
+/// The songs the library selection refers to: every marked item if there
+/// are any, otherwise the selected row of the active selector.
+pub fn selected_songs(model: &Model) -> Vec<Songs> {
+    let tags = &model.library_view().tags;
+    let library = |name: String, values: Vec<String>| Songs::Library {
+        tags: tags.clone(),
+        name,
+        values,
+    };
+    if !model.library.marked.is_empty() {
+        return model
+            .library
+            .marked
+            .iter()
+            .map(|mark| match mark {
+                LibraryMark::Album { artist, album } => {
+                    library(artist.clone(), album.clone())
+                }
+                LibraryMark::Song(file) => Songs::File(file.clone()),
+            })
+            .collect();
+    }
+    let Some(artist) = model.library.selected_item() else {
+        return Vec::new();
+    };
+    match model.library.active {
+        ArtistSelector => vec![library(artist.name.clone(), Vec::new())],
+        TrackSelector => match artist.selected_item().map(|i| i.item) {
+            Some(Album(album)) => album
+                .tracks
+                .iter()
+                .map(|s| Songs::File(s.file.clone()))
+                .collect(),
+            Some(Song(song)) => vec![Songs::File(song.file.clone())],
+            None => Vec::new(),
+        },
+    }
+}
+
 pub fn handle_library_track(model: &mut Model, msg: Message) -> Result<Update> {
+    let count = model.count();
     match msg {
+        Message::ToggleMark => {
+            model.library.toggle_mark();
+            if let Some(art) = model.library.selected_item_mut() {
+                handle_vertical(Vertical::Down, 1, art);
+            }
+            Ok(Update::empty())
+        }
         Message::Direction(Dirs::Vert(d)) => {
             if let Some(art) = model.library.selected_item_mut() {
-                handle_vertical(d, art);
+                handle_vertical(d, count, art);
             }
             Ok(Update::empty())
         }
@@ -215,7 +356,7 @@ pub fn handle_library_track(model: &mut Model, msg: Message) -> Result<Update> {
                 .track_select
                 .height;
             if let Some(art) = model.library.selected_item_mut() {
-                scroll_screenful(v, k.into(), art);
+                scroll_screenful(v, k.into(), count, art);
             }
             Ok(Update::empty())
         }
@@ -229,7 +370,7 @@ pub fn handle_library_track(model: &mut Model, msg: Message) -> Result<Update> {
         Message::SelectAndNext => {
             let res = add_item(model);
             if let Some(art) = model.library.selected_item_mut() {
-                handle_vertical(Vertical::Down, art);
+                handle_vertical(Vertical::Down, 1, art);
             }
             res
         }
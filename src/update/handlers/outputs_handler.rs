@@ -0,0 +1,46 @@
+use super::*;
+use crate::event_handler::Result;
+
+pub fn handle_outputs(model: &mut Model, msg: Message) -> Result<Update> {
+    let count = model.count();
+    let popup = match model.outputs.as_mut() {
+        Some(p) => p,
+        None => return Ok(Update::empty()),
+    };
+    match msg {
+        Message::Direction(Dirs::Vert(d)) => {
+            handle_vertical(d, count, popup);
+            Ok(Update::empty())
+        }
+        Message::Select | Message::Fold => {
+            if let Some(output) = popup.selected_output() {
+                model.conn.out_toggle(output.id)?;
+            }
+            Ok(Update::OUTPUTS)
+        }
+        Message::SelectAndNext => {
+            if let Some(output) = popup.selected_output() {
+                model.conn.out_toggle(output.id)?;
+            }
+            handle_vertical(Vertical::Down, 1, popup);
+            Ok(Update::OUTPUTS)
+        }
+        Message::Replace => {
+            // enable only the selected output
+            if let Some(selected) = popup.selected_output().map(|o| o.id) {
+                model.conn.out_enable(selected)?;
+                for output in &popup.contents {
+                    if output.id != selected && output.enabled {
+                        model.conn.out_disable(output.id)?;
+                    }
+                }
+            }
+            Ok(Update::OUTPUTS)
+        }
+        Message::Escape | Message::ToggleScreen => {
+            model.outputs = None;
+            Ok(Update::empty())
+        }
+        _ => Ok(Update::empty()),
+    }
+}
@@ -0,0 +1,224 @@
+use super::*;
+use crate::event_handler::Result;
+use crate::model::PlaylistActiveSelector::*;
+use crate::view::layout::playlist_layout::PlaylistLayout;
+use crate::view::layout::InoriLayout;
+
+pub fn handle_playlists(model: &mut Model, msg: Message) -> Result<Update> {
+    match msg {
+        Message::LocalSearch(SearchMsg::Start) => {
+            model.playlists.active = PlaylistSelector;
+            model.playlists.search.set_on();
+            if model.playlists.len() != 0 {
+                model.playlists.set_selected(Some(0))
+            }
+            model.state = State::Searching;
+            Ok(Update::empty())
+        }
+        Message::LocalSearch(SearchMsg::End) => {
+            model.state = State::Running;
+            Ok(Update::empty())
+        }
+        Message::Escape => {
+            let selected = model.playlists.selected_name();
+            model.playlists.search.set_off();
+            if let Some(name) = selected {
+                model.playlists.select_by_name(&name);
+            }
+            Ok(Update::empty())
+        }
+        Message::ToggleScreen => {
+            model.screen = Screen::Queue;
+            Ok(Update::empty())
+        }
+        Message::TogglePanel => {
+            model.playlists.active = match model.playlists.active {
+                PlaylistSelector => TrackSelector,
+                TrackSelector => PlaylistSelector,
+            };
+            Ok(Update::empty())
+        }
+        other => match model.playlists.active {
+            PlaylistSelector => handle_playlist_select(model, other),
+            TrackSelector => handle_playlist_tracks(model, other),
+        },
+    }
+}
+
+pub fn handle_search(model: &mut Model, k: SearchKey) -> Result<Update> {
+    if let Some(m) = handle_search_k(
+        &mut model.playlists,
+        k,
+        &mut model.matcher,
+        model.frame_size.height.into(),
+        None,
+    ) {
+        handle_msg(model, m)
+    } else {
+        Ok(Update::empty())
+    }
+}
+
+pub fn handle_playlist_select(
+    model: &mut Model,
+    msg: Message,
+) -> Result<Update> {
+    match msg {
+        Message::Direction(Dirs::Vert(d)) => {
+            handle_vertical(d, model.count(), &mut model.playlists);
+            Ok(Update::empty())
+        }
+        Message::ScrollScreenful(v) => {
+            let k = PlaylistLayout::new(model.frame_size, model)
+                .playlist_select
+                .height;
+            scroll_screenful(v, k.into(), model.count(), &mut model.playlists);
+            Ok(Update::empty())
+        }
+        Message::Direction(Dirs::Horiz(Horizontal::Right)) => {
+            model.playlists.active = TrackSelector;
+            if let Some(p) = model.playlists.selected_item_mut() {
+                p.init();
+            }
+            Ok(Update::empty())
+        }
+        Message::Select => load_playlist(model),
+        Message::SelectAndNext => {
+            let res = load_playlist(model);
+            handle_vertical(Vertical::Down, 1, &mut model.playlists);
+            res
+        }
+        Message::Replace => replace_with_playlist(model, None),
+        Message::Rename => {
+            if let Some(name) = model.playlists.selected_name() {
+                model.prompt =
+                    Some(Prompt::new(PromptKind::RenamePlaylist(name)));
+                model.state = State::Prompting;
+            }
+            Ok(Update::empty())
+        }
+        Message::Delete => {
+            if let Some(name) = model.playlists.selected_name() {
+                model.prompt =
+                    Some(Prompt::new(PromptKind::DeletePlaylist(name)));
+                model.state = State::Prompting;
+            }
+            Ok(Update::empty())
+        }
+        _ => Ok(Update::empty()),
+    }
+}
+
+pub fn handle_playlist_tracks(
+    model: &mut Model,
+    msg: Message,
+) -> Result<Update> {
+    let count = model.count();
+    match msg {
+        Message::Direction(Dirs::Vert(d)) => {
+            if let Some(p) = model.playlists.selected_item_mut() {
+                handle_vertical(d, count, p);
+            }
+            Ok(Update::empty())
+        }
+        Message::ScrollScreenful(v) => {
+            let k = PlaylistLayout::new(model.frame_size, model)
+                .track_select
+                .height;
+            if let Some(p) = model.playlists.selected_item_mut() {
+                scroll_screenful(v, k.into(), count, p);
+            }
+            Ok(Update::empty())
+        }
+        Message::Direction(Dirs::Horiz(Horizontal::Left)) => {
+            model.playlists.active = PlaylistSelector;
+            Ok(Update::empty())
+        }
+        Message::Select => add_track(model),
+        Message::SelectAndNext => {
+            let res = add_track(model);
+            if let Some(p) = model.playlists.selected_item_mut() {
+                handle_vertical(Vertical::Down, 1, p);
+            }
+            res
+        }
+        Message::Replace => {
+            let pos =
+                model.playlists.selected_item().and_then(|p| p.selected());
+            replace_with_playlist(model, pos)
+        }
+        Message::Delete => {
+            if let Some(p) = model.playlists.selected_item_mut() {
+                if let Some(pos) = p.selected() {
+                    model.conn.pl_delete(&p.name, pos as u32)?;
+                    p.fetched = false;
+                }
+            }
+            Ok(Update::empty())
+        }
+        _ => Ok(Update::empty()),
+    }
+}
+
+pub fn load_playlist(model: &mut Model) -> Result<Update> {
+    let before = model.status.queue_len;
+    if let Some(name) = model.playlists.selected_name() {
+        model.conn.load(&name, ..)?;
+    }
+    notify_added(model, before);
+    Ok(Update::STATUS
+        | Update::QUEUE
+        | Update::START_PLAYING
+        | Update::CURRENT_SONG)
+}
+
+pub fn add_track(model: &mut Model) -> Result<Update> {
+    let before = model.status.queue_len;
+    if let Some(song) = model
+        .playlists
+        .selected_item()
+        .and_then(|p| p.selected_track())
+    {
+        model.conn.push(song)?;
+    }
+    notify_added(model, before);
+    Ok(Update::STATUS
+        | Update::QUEUE
+        | Update::START_PLAYING
+        | Update::CURRENT_SONG)
+}
+
+/// Replaces the queue with the selected playlist and starts playing it,
+/// from the track at `pos` if given.
+pub fn replace_with_playlist(
+    model: &mut Model,
+    pos: Option<usize>,
+) -> Result<Update> {
+    if let Some(name) = model.playlists.selected_name() {
+        model.conn.clear()?;
+        model.conn.load(&name, ..)?;
+        model.conn.switch(pos.unwrap_or(0) as u32)?;
+        model.notify(format!("replaced the queue with \"{}\"", name));
+    }
+    Ok(Update::STATUS | Update::QUEUE | Update::CURRENT_SONG)
+}
+
+pub fn rename_playlist(
+    model: &mut Model,
+    from: &str,
+    to: &str,
+) -> Result<Update> {
+    if !to.is_empty() && from != to {
+        model.conn.pl_rename(from, to)?;
+        build_playlists::build_playlists(model)?;
+        model.playlists.select_by_name(to);
+        model.notify(format!("renamed \"{}\" to \"{}\"", from, to));
+    }
+    Ok(Update::empty())
+}
+
+pub fn delete_playlist(model: &mut Model, name: &str) -> Result<Update> {
+    model.conn.pl_remove(name)?;
+    model.notify(format!("deleted playlist \"{}\"", name));
+    Ok(Update::PLAYLISTS)
+}
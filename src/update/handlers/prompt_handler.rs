@@ -0,0 +1,94 @@
+use super::*;
+use crate::event_handler::Result;
+
+pub fn handle_prompt(model: &mut Model, k: KeyEvent) -> Result<Update> {
+    let prompt = match model.prompt.as_mut() {
+        Some(p) => p,
+        None => {
+            model.state = State::Running;
+            return Ok(Update::empty());
+        }
+    };
+    if prompt.is_confirm() {
+        match k.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                submit_prompt(model)
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                close_prompt(model);
+                Ok(Update::empty())
+            }
+            _ => Ok(Update::empty()),
+        }
+    } else if k.modifiers.contains(KeyModifiers::CONTROL) {
+        match k.code {
+            KeyCode::Char('u') => prompt.input.clear(),
+            KeyCode::Char('g') => close_prompt(model),
+            _ => {}
+        }
+        Ok(Update::empty())
+    } else {
+        if k.code != KeyCode::Tab {
+            prompt.clear_completions();
+        }
+        match k.code {
+            KeyCode::Char(c) => prompt.input.push(c),
+            KeyCode::Backspace => {
+                let _ = prompt.input.pop();
+            }
+            KeyCode::Esc => close_prompt(model),
+            KeyCode::Enter => return submit_prompt(model),
+            KeyCode::Tab => command_handler::complete(model)?,
+            _ => {}
+        }
+        Ok(Update::empty())
+    }
+}
+
+pub fn close_prompt(model: &mut Model) {
+    model.prompt = None;
+    model.state = State::Running;
+}
+
+fn submit_prompt(model: &mut Model) -> Result<Update> {
+    let prompt = match model.prompt.take() {
+        Some(p) => p,
+        None => return Ok(Update::empty()),
+    };
+    model.state = State::Running;
+    match prompt.kind {
+        PromptKind::RenamePlaylist(from) => {
+            playlist_handler::rename_playlist(model, &from, prompt.input.trim())
+        }
+        PromptKind::DeletePlaylist(name) => {
+            playlist_handler::delete_playlist(model, &name)
+        }
+        PromptKind::SaveQueue => {
+            queue_handler::save_queue(model, prompt.input.trim(), false)
+        }
+        PromptKind::OverwritePlaylist(name) => {
+            queue_handler::save_queue(model, &name, true)
+        }
+        PromptKind::MoveTo => {
+            queue_handler::move_selection_to(model, &prompt.input)
+        }
+        PromptKind::SetVolume => set_volume(model, prompt.input.trim()),
+        PromptKind::Command { .. } => {
+            command_handler::run_command(model, prompt.input.trim())
+        }
+    }
+}
+
+/// Sets the volume to an absolute value, or adjusts it when the input starts
+/// with `+` or `-`.
+pub fn set_volume(model: &mut Model, input: &str) -> Result<Update> {
+    let volume = match (input.chars().next(), input.parse::<i64>()) {
+        (Some('+') | Some('-'), Ok(delta)) if model.status.volume >= 0 => {
+            model.status.volume as i64 + delta
+        }
+        (Some('+') | Some('-'), _) | (_, Err(_)) => return Ok(Update::empty()),
+        (_, Ok(v)) => v,
+    };
+    model.conn.volume(volume.clamp(0, 100) as i8)?;
+    Ok(Update::STATUS)
+}
@@ -1,6 +1,7 @@
 use super::*;
 use crate::view::layout::InoriLayout;
 use crate::{event_handler::Result, view::layout::queue_layout::QueueLayout};
+use std::ops::Range;
 
 pub fn handle_queue(model: &mut Model, msg: Message) -> Result<Update> {
     match msg {
@@ -9,12 +10,12 @@ pub fn handle_queue(model: &mut Model, msg: Message) -> Result<Update> {
             Ok(Update::empty())
         }
         Message::Direction(Dirs::Vert(d)) => {
-            handle_vertical(d, &mut model.queue);
+            handle_vertical(d, model.count(), &mut model.queue);
             Ok(Update::empty())
         }
         Message::ScrollScreenful(v) => {
             let k = QueueLayout::new(model.frame_size, model).queue.height;
-            scroll_screenful(v, k.into(), &mut model.queue);
+            scroll_screenful(v, k.into(), model.count(), &mut model.queue);
             Ok(Update::empty())
         }
         Message::Select => {
@@ -25,34 +26,44 @@ pub fn handle_queue(model: &mut Model, msg: Message) -> Result<Update> {
             }
             Ok(Update::STATUS | Update::CURRENT_SONG)
         }
-        Message::Direction(Dirs::Horiz(d)) => {
-            if model.queue.len() >= 2 {
-                if let Some(p) = model.queue.selected() {
-                    let to = match d {
-                        Horizontal::Left => {
-                            safe_subtract(p, 1, model.queue.len())
-                        }
-                        Horizontal::Right => safe_add(p, 1, model.queue.len()),
-                    };
-                    model.conn.swap(p as u32, to as u32)?;
-                    model.queue.set_selected(Some(to));
-                    model.queue.watch_oob();
-                }
-            }
+        Message::MoveUp => {
+            move_selection(model, Horizontal::Left)?;
+            Ok(Update::STATUS | Update::QUEUE)
+        }
+        Message::MoveDown => {
+            move_selection(model, Horizontal::Right)?;
             Ok(Update::STATUS | Update::QUEUE)
         }
         Message::Delete => {
+            let songs = selected_positions(model, model.count());
             if let Some(p) = model.queue.selected() {
-                model.conn.delete(p as u32)?;
-                model.queue.set_selected(Some(safe_subtract(
-                    p,
-                    1,
-                    model.queue.len() - 1,
-                )));
+                // delete from the back so the earlier ranges stay valid
+                for run in position_runs(&songs).into_iter().rev() {
+                    model.conn.delete(run)?;
+                }
+                model.queue.clear_marks();
+                let len = model.queue.len().saturating_sub(songs.len());
+                model.queue.set_selected(Some(safe_subtract(p, 1, len)));
                 model.queue.watch_oob();
             }
             Ok(Update::STATUS | Update::QUEUE)
         }
+        Message::ToggleMark => {
+            model.queue.toggle_mark();
+            handle_vertical(Vertical::Down, 1, &mut model.queue);
+            Ok(Update::empty())
+        }
+        Message::Visual => {
+            model.queue.toggle_visual();
+            Ok(Update::empty())
+        }
+        Message::MoveTo => {
+            if model.queue.selected().is_some() {
+                model.prompt = Some(Prompt::new(PromptKind::MoveTo));
+                model.state = State::Prompting;
+            }
+            Ok(Update::empty())
+        }
         Message::LocalSearch(SearchMsg::Start) => {
             model.queue.search.active = true;
             model.state = State::Searching;
@@ -68,21 +79,165 @@ pub fn handle_queue(model: &mut Model, msg: Message) -> Result<Update> {
         Message::Escape => {
             model.queue.search.active = false;
             model.queue.search.query = String::new();
+            model.queue.clear_marks();
             Ok(Update::empty())
         }
         _ => Ok(Update::empty()),
     }
 }
 
-pub fn handle_search(model: &mut Model, k: KeyEvent) -> Result<Update> {
+pub fn handle_search(model: &mut Model, k: SearchKey) -> Result<Update> {
     if let Some(m) = handle_search_k(
         &mut model.queue,
         k,
         &mut model.matcher,
         model.frame_size.height.into(),
+        Some(&mut model.history.queue),
     ) {
         handle_msg(model, m)
     } else {
         Ok(Update::empty())
     }
 }
+
+/// The queue positions of the songs an action applies to, in order.
+fn selected_positions(model: &Model, count: usize) -> Vec<u32> {
+    model
+        .queue
+        .selection(count)
+        .iter()
+        .filter_map(|s| s.place.map(|p| p.pos))
+        .collect()
+}
+
+/// Splits sorted queue positions into runs of consecutive positions, so that
+/// each run goes to mpd as a single range.
+fn position_runs(positions: &[u32]) -> Vec<Range<u32>> {
+    let mut runs: Vec<Range<u32>> = Vec::new();
+    for &pos in positions {
+        match runs.last_mut() {
+            Some(run) if run.end == pos => run.end += 1,
+            _ => runs.push(pos..pos + 1),
+        }
+    }
+    runs
+}
+
+/// Moves the selected songs one place up or down, keeping their order.
+/// Songs already at the edge of the queue stay in place.
+pub fn move_selection(model: &mut Model, d: Horizontal) -> Result<()> {
+    let songs = selected_positions(model, 1);
+    let len = model.queue.contents.len() as u32;
+    if songs.is_empty() || len < 2 {
+        return Ok(());
+    }
+    let cursor = model.queue.selected().unwrap_or(0);
+    // the runs are separated by unselected songs, so moving one run past
+    // its neighbour leaves the positions of the others unchanged
+    let runs = position_runs(&songs);
+    let moved = match d {
+        Horizontal::Left => {
+            for run in runs.iter().filter(|r| r.start > 0) {
+                model.conn.shift(run.clone(), (run.start - 1) as usize)?;
+            }
+            songs.first().is_some_and(|pos| *pos > 0)
+        }
+        Horizontal::Right => {
+            for run in runs.iter().rev().filter(|r| r.end < len) {
+                model.conn.shift(run.clone(), (run.start + 1) as usize)?;
+            }
+            songs.last().is_some_and(|pos| *pos < len - 1)
+        }
+    };
+    let len = len as usize;
+    if moved && !model.queue.should_filter() {
+        model.queue.set_selected(Some(match d {
+            Horizontal::Left => safe_subtract(cursor, 1, len),
+            Horizontal::Right => safe_add(cursor, 1, len),
+        }));
+    }
+    Ok(())
+}
+
+/// Moves the selected songs so that the first of them lands on the given
+/// 1-indexed queue position, keeping their order.
+pub fn move_selection_to(model: &mut Model, input: &str) -> Result<Update> {
+    let target = match input.trim().parse::<usize>() {
+        Ok(t) if t > 0 => t - 1,
+        _ => return Ok(Update::empty()),
+    };
+    let songs = selected_positions(model, 1);
+    let len = model.queue.contents.len() as u32;
+    if songs.is_empty() {
+        return Ok(Update::empty());
+    }
+    let n = songs.len() as u32;
+    let target = target.min((len - n) as usize);
+    let runs = position_runs(&songs);
+    let mut range = runs[0].clone();
+    if runs.len() > 1 {
+        // gather the runs at the end of the queue, back to front, so they
+        // form a single range
+        let mut end = len;
+        for run in runs.iter().rev() {
+            let start = end - (run.end - run.start);
+            if run.start != start {
+                model.conn.shift(run.clone(), start as usize)?;
+            }
+            end = start;
+        }
+        range = len - n..len;
+    }
+    if range.start as usize != target {
+        model.conn.shift(range, target)?;
+    }
+    model.queue.clear_marks();
+    if !model.queue.should_filter() {
+        model.queue.set_selected(Some(target));
+    }
+    Ok(Update::STATUS | Update::QUEUE)
+}
+
+/// Saves the current queue as a stored playlist. If a playlist with the same
+/// name exists and `overwrite` is not set, asks for confirmation first.
+pub fn save_queue(
+    model: &mut Model,
+    name: &str,
+    overwrite: bool,
+) -> Result<Update> {
+    if name.is_empty() {
+        return Ok(Update::empty());
+    }
+    let playlists = model.conn.playlists()?;
+    let exists = playlists.iter().any(|p| p.name == name);
+    if exists && !overwrite {
+        model.prompt =
+            Some(Prompt::new(PromptKind::OverwritePlaylist(name.into())));
+        model.state = State::Prompting;
+        return Ok(Update::empty());
+    }
+    if exists {
+        // save under a temporary name first, so that the old playlist is
+        // only removed once the new one exists
+        let tmp = (0..)
+            .map(|i| match i {
+                0 => format!("{}.inori-tmp", name),
+                i => format!("{}.inori-tmp{}", name, i),
+            })
+            .find(|tmp| playlists.iter().all(|p| p.name != *tmp))
+            .unwrap_or_default();
+        model.conn.save(tmp.as_str())?;
+        model.conn.pl_remove(name)?;
+        if let Err(e) = model.conn.pl_rename(tmp.as_str(), name) {
+            model.notify_error(format!(
+                "saved the queue as \"{}\", renaming to \"{}\" failed: {}",
+                tmp, name, e
+            ));
+            return Ok(Update::PLAYLISTS);
+        }
+    } else {
+        model.conn.save(name)?;
+    }
+    model.notify(format!("saved the queue as \"{}\"", name));
+    Ok(Update::PLAYLISTS)
+}
@@ -0,0 +1,93 @@
+use super::*;
+use crate::event_handler::Result;
+use crate::view::layout::track_search_layout::TrackSearchLayout;
+use crate::view::layout::InoriLayout;
+
+pub fn handle_track_search(model: &mut Model, msg: Message) -> Result<Update> {
+    match msg {
+        Message::LocalSearch(SearchMsg::Start) => {
+            model.state = State::Searching;
+            Ok(Update::empty())
+        }
+        Message::LocalSearch(SearchMsg::End) => {
+            model.state = State::Running;
+            Ok(Update::empty())
+        }
+        Message::Escape => {
+            model.track_search.clear();
+            Ok(Update::empty())
+        }
+        Message::ToggleScreen => {
+            model.screen = Screen::Queue;
+            Ok(Update::empty())
+        }
+        Message::Direction(Dirs::Vert(d)) => {
+            handle_vertical(d, model.count(), &mut model.track_search);
+            Ok(Update::empty())
+        }
+        Message::ScrollScreenful(v) => {
+            let k = TrackSearchLayout::new(model.frame_size, model)
+                .listing
+                .height;
+            scroll_screenful(
+                v,
+                k.into(),
+                model.count(),
+                &mut model.track_search,
+            );
+            Ok(Update::empty())
+        }
+        Message::Select => add_song(model),
+        Message::SelectAndNext => {
+            let res = add_song(model);
+            handle_vertical(Vertical::Down, 1, &mut model.track_search);
+            res
+        }
+        Message::InsertNext | Message::PlayNow => {
+            if let Some(song) = model.track_search.selected_song() {
+                let songs = vec![Songs::File(song.file.clone())];
+                insert_next(model, songs, msg == Message::PlayNow);
+            }
+            Ok(Update::empty())
+        }
+        _ => Ok(Update::empty()),
+    }
+}
+
+/// Edits the query, which is sent to mpd once typing pauses. Enter sends it
+/// right away and stops typing.
+pub fn handle_search(model: &mut Model, k: SearchKey) -> Result<Update> {
+    match k {
+        SearchKey::Bound(Message::Direction(Dirs::Vert(d))) => {
+            handle_vertical(d, 1, &mut model.track_search);
+        }
+        SearchKey::Bound(Message::Select) => {
+            send_track_search(model, Duration::ZERO);
+            model.state = State::Running;
+        }
+        SearchKey::Bound(Message::Escape) => {
+            model.state = State::Running;
+        }
+        SearchKey::Bound(m) => return handle_msg(model, m),
+        SearchKey::Typed(k) => {
+            edit_query(&mut model.track_search.query, k);
+            model.track_search.dirty = true;
+            model.track_search.typed_at = Instant::now();
+        }
+    }
+    Ok(Update::empty())
+}
+
+/// Appends the selected song to the queue.
+fn add_song(model: &mut Model) -> Result<Update> {
+    let Some(song) = model.track_search.selected_song().cloned() else {
+        return Ok(Update::empty());
+    };
+    let before = model.status.queue_len;
+    model.conn.push(&song)?;
+    notify_added(model, before);
+    Ok(Update::STATUS
+        | Update::QUEUE
+        | Update::START_PLAYING
+        | Update::CURRENT_SONG)
+}
@@ -1,18 +1,17 @@
+use super::build_files;
 use super::build_library;
+use super::build_playlists;
 use crate::event_handler::Result;
 use crate::model::*;
 use proto::*;
 
 pub fn update_library(model: &mut Model) -> Result<()> {
     model.library.watch_oob();
-    if model.library.contents.is_empty() {
-        build_library::build_library(model)?;
-    }
     if model.library.len() != 0 && model.library.selected().is_none() {
         model.library.set_selected(Some(0))
     }
     if !model.library.selected_item().is_some_and(|i| i.fetched) {
-        build_library::add_tracks(model)?;
+        build_library::add_tracks(model);
     }
     Ok(())
 }
@@ -26,5 +25,38 @@ pub fn update_queue(model: &mut Model) -> Result<()> {
     if model.queue.contents.is_empty() {
         model.queue.set_selected(None);
     }
+    model.queue.retain_marks();
+    Ok(())
+}
+
+pub fn update_playlists(model: &mut Model) -> Result<()> {
+    if !model.playlists.fetched {
+        build_playlists::build_playlists(model)?;
+    }
+    model.playlists.watch_oob();
+    if model.playlists.len() != 0 && model.playlists.selected().is_none() {
+        model.playlists.set_selected(Some(0))
+    }
+    if model.playlists.selected_item().is_some_and(|p| !p.fetched) {
+        build_playlists::add_playlist_tracks(model)?;
+    }
+    if let Some(playlist) = model.playlists.selected_item_mut() {
+        playlist.watch_oob();
+        playlist.init();
+    }
+    Ok(())
+}
+
+pub fn update_files(model: &mut Model) -> Result<()> {
+    if !model.files.fetched {
+        build_files::build_directory(model)?;
+    }
+    model.files.init();
+    Ok(())
+}
+
+pub fn update_track_search(model: &mut Model) -> Result<()> {
+    model.track_search.watch_oob();
+    model.track_search.init();
     Ok(())
 }
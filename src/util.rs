@@ -30,6 +30,17 @@ pub fn song_album(s: &Song) -> Option<&String> {
     Some(&s.tags.iter().find(|t| t.0 == "Album")?.1)
 }
 
+/// The value of a tag of the song, named in any case.
+pub fn song_tag<'a>(s: &'a Song, tag: &str) -> Option<&'a String> {
+    if tag.eq_ignore_ascii_case("artist") {
+        s.artist.as_ref()
+    } else if tag.eq_ignore_ascii_case("title") {
+        s.title.as_ref()
+    } else {
+        Some(&s.tags.iter().find(|t| t.0.eq_ignore_ascii_case(tag))?.1)
+    }
+}
+
 /// Formats a duration as HH:MM:SS or MM:SS as needed.
 pub fn format_time(d: Duration) -> String {
     let total = d.as_secs();
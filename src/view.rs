@@ -1,4 +1,5 @@
 use crate::config::deserialize_style;
+use crate::config::keybind::KeybindTarget;
 use crate::config::ConfigError;
 use crate::model::*;
 use ratatui::prelude::*;
@@ -8,12 +9,20 @@ use std::error::Error;
 use toml::Table;
 use toml::Value;
 mod artist_select_renderer;
+pub mod files_renderer;
+mod help_renderer;
 pub mod layout;
 pub mod library_renderer;
+mod notification_renderer;
+mod outputs_renderer;
+pub mod playlist_renderer;
+mod prompt_renderer;
 pub mod queue_renderer;
 mod search_renderer;
 mod status_renderer;
+mod track_search_renderer;
 mod track_select_renderer;
+mod which_key_renderer;
 
 type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
@@ -22,8 +31,12 @@ pub struct Theme {
     pub block_active: Style,
     pub field_album: Style,
     pub field_artistsort: Style,
+    pub field_directory: Style,
     pub item_highlight_active: Style,
     pub item_highlight_inactive: Style,
+    pub item_marked: Style,
+    pub notification_error: Style,
+    pub notification_info: Style,
     pub progress_bar_filled: Style,
     pub progress_bar_unfilled: Style,
     pub search_query_active: Style,
@@ -42,8 +55,12 @@ impl Theme {
             block_active: Style::default().fg(Red),
             field_album: Style::default().bold().italic().fg(Red),
             field_artistsort: Style::default().fg(DarkGray),
+            field_directory: Style::default().bold().fg(Blue),
             item_highlight_active: Style::default().fg(Black).bg(White),
             item_highlight_inactive: Style::default().fg(Black).bg(DarkGray),
+            item_marked: Style::default().fg(Black).bg(Yellow),
+            notification_error: Style::default().fg(White).bg(Red),
+            notification_info: Style::default().fg(Black).bg(Cyan),
             progress_bar_filled: Style::default()
                 .fg(LightYellow)
                 .bg(Black)
@@ -70,6 +87,15 @@ impl Theme {
                 ("item_highlight_inactive", Value::Table(t)) => {
                     self.item_highlight_inactive = deserialize_style(t)?;
                 }
+                ("item_marked", Value::Table(t)) => {
+                    self.item_marked = deserialize_style(t)?;
+                }
+                ("notification_error", Value::Table(t)) => {
+                    self.notification_error = deserialize_style(t)?;
+                }
+                ("notification_info", Value::Table(t)) => {
+                    self.notification_info = deserialize_style(t)?;
+                }
                 ("block_active", Value::Table(t)) => {
                     self.block_active = deserialize_style(t)?;
                 }
@@ -94,6 +120,9 @@ impl Theme {
                 ("field_album", Value::Table(t)) => {
                     self.field_album = deserialize_style(t)?;
                 }
+                ("field_directory", Value::Table(t)) => {
+                    self.field_directory = deserialize_style(t)?;
+                }
                 ("playing", Value::Table(t)) => {
                     self.status_playing = deserialize_style(t)?;
                 }
@@ -153,5 +182,37 @@ pub fn view(model: &mut Model, frame: &mut Frame) {
     match model.screen {
         Screen::Library => library_renderer::render(model, frame, &theme),
         Screen::Queue => queue_renderer::render(model, frame, &theme),
+        Screen::Playlists => playlist_renderer::render(model, frame, &theme),
+        Screen::Files => files_renderer::render(model, frame, &theme),
+        Screen::TrackSearch => {
+            track_search_renderer::render(model, frame, &theme)
+        }
+    }
+    if let Some(notification) = model.current_notification() {
+        notification_renderer::render_notification(notification, frame, &theme);
+    }
+    if let Some(popup) = model.outputs.as_mut() {
+        outputs_renderer::render_outputs(popup, frame, &theme);
+    }
+    if let Some(popup) = model.help.as_mut() {
+        let searching = matches!(model.state, State::Searching);
+        help_renderer::render_help(popup, searching, frame, &theme);
+    }
+    if let (State::Running, Some(KeybindTarget::Map(continuations))) = (
+        &model.state,
+        model
+            .config
+            .keybindings
+            .lookup(model.key_context(), &model.parse_state),
+    ) {
+        which_key_renderer::render_which_key(
+            &model.parse_state,
+            continuations,
+            frame,
+            &theme,
+        );
+    }
+    if let Some(prompt) = &model.prompt {
+        prompt_renderer::render_prompt(prompt, frame, &theme);
     }
 }
@@ -57,6 +57,15 @@ pub fn get_artist_list<'a>(model: &Model, theme: &Theme) -> List<'a> {
     }
 }
 
+/// The name of the library view, capitalized for the panel title.
+fn view_title(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 pub fn render_artist_list(
     model: &mut Model,
     frame: &mut Frame,
@@ -71,7 +80,7 @@ pub fn render_artist_list(
                 }
                 TrackSelector => Block::bordered(),
             }
-            .title("Artists"),
+            .title(view_title(&model.library_view().name)),
         )
         .highlight_style(match model.library.active {
             ArtistSelector => theme.item_highlight_active,
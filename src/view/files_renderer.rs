@@ -0,0 +1,86 @@
+use super::artist_select_renderer::render_str_with_idxs;
+use super::layout::files_layout::FilesLayout;
+use super::layout::InoriLayout;
+use super::search_renderer::make_search_box;
+use super::status_renderer::render_status;
+use super::Theme;
+use crate::model::proto::*;
+use crate::model::*;
+use crate::util::format_time;
+use ratatui::prelude::Constraint::*;
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+fn entry_to_row<'a>(
+    entry: &FileEntry,
+    idxs: Option<&Vec<u32>>,
+    theme: &Theme,
+) -> Row<'a> {
+    let name = entry.name().to_string();
+    let mut name_line: Vec<Span> = match idxs {
+        Some(idxs) => {
+            let len = name.chars().count();
+            render_str_with_idxs(name, idxs, len, theme)
+        }
+        None => vec![Span::from(name)],
+    };
+    match entry {
+        FileEntry::Directory(_) => {
+            name_line.push(Span::from("/"));
+            Row::new(vec![
+                Cell::from(Line::from(name_line)),
+                Cell::from(""),
+                Cell::from(""),
+            ])
+            .style(theme.field_directory)
+        }
+        FileEntry::Song(song) => Row::new(vec![
+            Cell::from(Line::from(name_line)),
+            Cell::from(
+                Line::from(song.title.clone().unwrap_or_default())
+                    .style(theme.status_title),
+            ),
+            Cell::from(
+                Line::from(song.duration.map(format_time).unwrap_or_default())
+                    .right_aligned(),
+            ),
+        ]),
+    }
+}
+
+pub fn render(model: &mut Model, frame: &mut Frame, theme: &Theme) {
+    let layout = FilesLayout::new(frame.area(), model);
+    render_status(model, frame, layout.header, theme);
+
+    if let Some(a) = layout.search {
+        frame.render_widget(
+            make_search_box(
+                &model.files.search.query,
+                matches!(model.state, State::Searching),
+                theme,
+            ),
+            a,
+        );
+    }
+
+    let rows: Vec<Row> = if model.files.should_filter() {
+        model
+            .files
+            .contents()
+            .zip(&model.files.search.cache.indices)
+            .map(|(entry, idxs)| entry_to_row(entry, Some(idxs), theme))
+            .collect()
+    } else {
+        model
+            .files
+            .contents()
+            .map(|entry| entry_to_row(entry, None, theme))
+            .collect()
+    };
+    let table = Table::new(rows, vec![Percentage(55), Percentage(45), Min(7)])
+        .row_highlight_style(theme.item_highlight_active)
+        .block(
+            Block::bordered().title(format!("/{}", model.files.current_dir())),
+        );
+    frame.render_stateful_widget(table, layout.listing, &mut model.files.state);
+}
@@ -0,0 +1,86 @@
+use super::artist_select_renderer::render_str_with_idxs;
+use super::layout::center_popup;
+use super::search_renderer::make_search_box;
+use super::Theme;
+use crate::model::proto::*;
+use crate::model::*;
+use ratatui::prelude::Constraint::*;
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+pub fn render_help(
+    popup: &mut HelpPopup,
+    searching: bool,
+    frame: &mut Frame,
+    theme: &Theme,
+) {
+    let search_height = if popup.search.active { 3 } else { 0 };
+    let height = (popup.len() as u16 + 2 + search_height)
+        .min(frame.area().height.saturating_sub(4));
+    let area = center_popup(frame.area(), height);
+    let [search_area, table_area] =
+        Layout::vertical([Length(search_height), Fill(1)]).areas(area);
+
+    let indices = &popup.search.cache.indices;
+    let filtering = popup.should_filter();
+    let name_width = popup
+        .contents
+        .iter()
+        .map(|e| e.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let rows: Vec<Row> = popup
+        .contents()
+        .enumerate()
+        .map(|(i, entry)| {
+            // the search string is the name and the keys, split by a space
+            let name_len = entry.name.chars().count();
+            let idxs: &[u32] = match indices.get(i) {
+                Some(idxs) if filtering => idxs,
+                _ => &[],
+            };
+            let key_idxs: Vec<u32> = idxs
+                .iter()
+                .filter(|&&j| j as usize > name_len)
+                .map(|j| j - name_len as u32 - 1)
+                .collect();
+            Row::new(vec![
+                Cell::from(Line::from(render_str_with_idxs(
+                    entry.name.clone(),
+                    idxs,
+                    name_len,
+                    theme,
+                ))),
+                Cell::from(
+                    Line::from(render_str_with_idxs(
+                        entry.keys.join(", "),
+                        &key_idxs,
+                        usize::MAX,
+                        theme,
+                    ))
+                    .style(theme.field_album),
+                ),
+            ])
+        })
+        .collect();
+
+    frame.render_widget(Clear, area);
+    if popup.search.active {
+        frame.render_widget(
+            make_search_box(&popup.search.query, searching, theme),
+            search_area,
+        );
+    }
+    frame.render_stateful_widget(
+        Table::new(rows, vec![Length(name_width as u16 + 2), Fill(1)])
+            .row_highlight_style(theme.item_highlight_active)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .border_style(theme.block_active)
+                    .title("Keybindings"),
+            ),
+        table_area,
+        &mut popup.state,
+    );
+}
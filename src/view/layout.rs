@@ -1,8 +1,26 @@
+pub mod files_layout;
 pub mod library_layout;
+pub mod playlist_layout;
 pub mod queue_layout;
+pub mod track_search_layout;
 use crate::model::*;
 use crate::view::Rect;
+use ratatui::prelude::Constraint::*;
+use ratatui::prelude::Layout;
 
 pub trait InoriLayout {
     fn new(frame_rect: Rect, model: &Model) -> Self;
 }
+
+/// A popup of the given height, horizontally centered in the frame.
+pub fn center_popup(frame_rect: Rect, height: u16) -> Rect {
+    let h = Layout::horizontal(vec![
+        Percentage(20),
+        Percentage(60),
+        Percentage(20),
+    ])
+    .split(frame_rect);
+    let v =
+        Layout::vertical(vec![Fill(1), Length(height), Fill(1)]).split(h[1]);
+    v[1]
+}
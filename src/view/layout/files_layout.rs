@@ -0,0 +1,28 @@
+use crate::model::*;
+use crate::view::layout::InoriLayout;
+use ratatui::prelude::Constraint::*;
+use ratatui::prelude::*;
+
+#[derive(Default)]
+pub struct FilesLayout {
+    pub header: Rect,
+    pub search: Option<Rect>,
+    pub listing: Rect,
+}
+
+impl InoriLayout for FilesLayout {
+    fn new(frame_rect: Rect, model: &Model) -> Self {
+        let mut new = FilesLayout::default();
+        let layout = Layout::vertical(vec![Max(4), Min(1)]).split(frame_rect);
+        let content = Layout::vertical(vec![Max(3), Min(1)]).split(layout[1]);
+        new.header = layout[0];
+
+        if model.files.search.active {
+            new.search = Some(content[0]);
+            new.listing = content[1];
+        } else {
+            new.listing = layout[1];
+        }
+        new
+    }
+}
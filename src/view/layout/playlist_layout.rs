@@ -0,0 +1,33 @@
+use crate::model::*;
+use crate::view::layout::InoriLayout;
+use ratatui::prelude::Constraint::*;
+use ratatui::prelude::*;
+
+#[derive(Default)]
+pub struct PlaylistLayout {
+    pub header: Rect,
+    pub playlist_select: Rect,
+    pub playlist_search: Option<Rect>,
+    pub track_select: Rect,
+}
+
+impl InoriLayout for PlaylistLayout {
+    fn new(frame_rect: Rect, model: &Model) -> Self {
+        let mut new = PlaylistLayout::default();
+        let layout = Layout::vertical(vec![Max(4), Min(1)]).split(frame_rect);
+        let menu_layout =
+            Layout::horizontal(vec![Ratio(1, 3), Ratio(2, 3)]).split(layout[1]);
+        let left_panel =
+            Layout::vertical(vec![Max(3), Min(1)]).split(menu_layout[0]);
+
+        new.header = layout[0];
+        if model.playlists.search.active {
+            new.playlist_select = left_panel[1];
+            new.playlist_search = Some(left_panel[0]);
+        } else {
+            new.playlist_select = menu_layout[0];
+        }
+        new.track_select = menu_layout[1];
+        new
+    }
+}
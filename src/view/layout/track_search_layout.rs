@@ -0,0 +1,23 @@
+use crate::model::*;
+use crate::view::layout::InoriLayout;
+use ratatui::prelude::Constraint::*;
+use ratatui::prelude::*;
+
+#[derive(Default)]
+pub struct TrackSearchLayout {
+    pub header: Rect,
+    pub search: Rect,
+    pub listing: Rect,
+}
+
+impl InoriLayout for TrackSearchLayout {
+    fn new(frame_rect: Rect, _model: &Model) -> Self {
+        let layout =
+            Layout::vertical(vec![Max(4), Max(3), Min(1)]).split(frame_rect);
+        TrackSearchLayout {
+            header: layout[0],
+            search: layout[1],
+            listing: layout[2],
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use super::Theme;
+use crate::model::*;
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+/// Draws the latest notification over the last line of the screen.
+pub fn render_notification(
+    notification: &Notification,
+    frame: &mut Frame,
+    theme: &Theme,
+) {
+    let area = frame.area();
+    if area.height == 0 {
+        return;
+    }
+    let line = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+    let style = match notification.level {
+        NotificationLevel::Info => theme.notification_info,
+        NotificationLevel::Error => theme.notification_error,
+    };
+    frame.render_widget(Clear, line);
+    frame.render_widget(
+        Paragraph::new(format!(" {} ", notification.text)).style(style),
+        line,
+    );
+}
@@ -0,0 +1,38 @@
+use super::layout::center_popup;
+use super::Theme;
+use crate::model::*;
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+pub fn render_outputs(
+    popup: &mut OutputsPopup,
+    frame: &mut Frame,
+    theme: &Theme,
+) {
+    let area = center_popup(frame.area(), popup.contents.len() as u16 + 2);
+    let items: Vec<ListItem> = popup
+        .contents
+        .iter()
+        .map(|output| {
+            ListItem::new(Line::from(vec![
+                Span::from(if output.enabled { "[x] " } else { "[ ] " }),
+                Span::from(output.name.clone()),
+                Span::from(format!(" ({})", output.plugin))
+                    .style(theme.field_album),
+            ]))
+        })
+        .collect();
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(
+        List::new(items)
+            .highlight_style(theme.item_highlight_active)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .border_style(theme.block_active)
+                    .title("Outputs"),
+            ),
+        area,
+        &mut popup.state,
+    );
+}
@@ -0,0 +1,137 @@
+use super::artist_select_renderer::render_str_with_idxs;
+use super::layout::playlist_layout::PlaylistLayout;
+use super::layout::InoriLayout;
+use super::search_renderer::make_search_box;
+use super::status_renderer::render_status;
+use super::Theme;
+use crate::model::proto::*;
+use crate::model::PlaylistActiveSelector::*;
+use crate::model::*;
+use crate::util::{format_time, song_album};
+use ratatui::prelude::Constraint::*;
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+use std::time::Duration;
+
+pub fn get_playlist_list<'a>(model: &Model, theme: &Theme) -> List<'a> {
+    if model.playlists.should_filter() {
+        let indices = &model.playlists.search.cache.indices;
+        List::new(model.playlists.contents().zip(indices).map(
+            |(playlist, idxs)| {
+                Line::from(render_str_with_idxs(
+                    playlist.name.clone(),
+                    idxs,
+                    playlist.name.chars().count(),
+                    theme,
+                ))
+            },
+        ))
+    } else {
+        List::new(
+            model
+                .playlists
+                .contents()
+                .map(|p| p.name.clone())
+                .collect::<Vec<String>>(),
+        )
+    }
+}
+
+pub fn make_track_table<'a>(
+    playlist: Option<&PlaylistData>,
+    theme: &Theme,
+) -> Table<'a> {
+    let rows: Vec<Row> = playlist
+        .map(|p| p.tracks.iter())
+        .into_iter()
+        .flatten()
+        .map(|song| {
+            Row::new(vec![
+                Cell::from(song.title.clone().unwrap_or(song.file.clone())),
+                Cell::from(
+                    Text::from(
+                        song.artist.clone().unwrap_or("Unknown Artist".into()),
+                    )
+                    .style(theme.status_artist),
+                ),
+                Cell::from(
+                    Text::from(
+                        song_album(song)
+                            .cloned()
+                            .unwrap_or("Unknown Album".into()),
+                    )
+                    .style(theme.field_album),
+                ),
+                Cell::from(
+                    Line::from(format_time(
+                        song.duration.unwrap_or(Duration::from_secs(0)),
+                    ))
+                    .right_aligned(),
+                ),
+            ])
+        })
+        .collect();
+    Table::new(
+        rows,
+        vec![Percentage(45), Percentage(25), Percentage(30), Min(7)],
+    )
+}
+
+pub fn render(model: &mut Model, frame: &mut Frame, theme: &Theme) {
+    let layout = PlaylistLayout::new(frame.area(), model);
+    render_status(model, frame, layout.header, theme);
+
+    if let Some(a) = layout.playlist_search {
+        frame.render_widget(
+            make_search_box(
+                &model.playlists.search.query,
+                matches!(model.state, State::Searching),
+                theme,
+            ),
+            a,
+        );
+    }
+
+    let list = get_playlist_list(model, theme)
+        .block(
+            match model.playlists.active {
+                PlaylistSelector => {
+                    Block::bordered().border_style(theme.block_active)
+                }
+                TrackSelector => Block::bordered(),
+            }
+            .title("Playlists"),
+        )
+        .highlight_style(match model.playlists.active {
+            PlaylistSelector => theme.item_highlight_active,
+            TrackSelector => theme.item_highlight_inactive,
+        });
+    frame.render_stateful_widget(
+        list,
+        layout.playlist_select,
+        &mut model.playlists.playlist_state,
+    );
+
+    let table = make_track_table(model.playlists.selected_item(), theme)
+        .block(
+            match model.playlists.active {
+                PlaylistSelector => Block::bordered(),
+                TrackSelector => {
+                    Block::bordered().border_style(theme.block_active)
+                }
+            }
+            .title("Tracks"),
+        )
+        .row_highlight_style(match model.playlists.active {
+            PlaylistSelector => theme.item_highlight_inactive,
+            TrackSelector => theme.item_highlight_active,
+        });
+    match model.playlists.selected_item_mut() {
+        Some(p) => frame.render_stateful_widget(
+            table,
+            layout.track_select,
+            &mut p.track_state,
+        ),
+        None => frame.render_widget(table, layout.track_select),
+    }
+}
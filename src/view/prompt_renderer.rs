@@ -0,0 +1,61 @@
+use super::layout::center_popup;
+use super::Theme;
+use crate::model::*;
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+pub fn render_prompt(prompt: &Prompt, frame: &mut Frame, theme: &Theme) {
+    if let PromptKind::Command { completions } = &prompt.kind {
+        return render_command_line(prompt, completions, frame, theme);
+    }
+    let area = center_popup(frame.area(), 3);
+    let line = if prompt.is_confirm() {
+        Line::from("")
+    } else {
+        Line::from(vec![
+            Span::from("> "),
+            Span::from(prompt.input.as_str()).style(theme.search_query_active),
+        ])
+    };
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(line).block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .border_style(theme.block_active)
+                .title(prompt.title()),
+        ),
+        area,
+    );
+}
+
+/// Draws the command line over the last line of the screen, with the
+/// candidates of the last completion on the line above it.
+fn render_command_line(
+    prompt: &Prompt,
+    completions: &[String],
+    frame: &mut Frame,
+    theme: &Theme,
+) {
+    let area = frame.area();
+    if area.height < 2 {
+        return;
+    }
+    let line = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+    frame.render_widget(Clear, line);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::from(":"),
+            Span::from(prompt.input.as_str()).style(theme.search_query_active),
+        ])),
+        line,
+    );
+    if !completions.is_empty() {
+        let above = Rect::new(area.x, line.y - 1, area.width, 1);
+        frame.render_widget(Clear, above);
+        frame.render_widget(
+            Paragraph::new(completions.join("  ")).style(theme.block_active),
+            above,
+        );
+    }
+}
@@ -13,6 +13,7 @@ use std::time::Duration;
 use super::status_renderer::render_status;
 
 pub fn make_queue<'a>(model: &mut Model, theme: &Theme) -> Table<'a> {
+    let marked = model.queue.marked_ids();
     let rows: Vec<Row> = model
         .queue
         .contents()
@@ -42,6 +43,11 @@ pub fn make_queue<'a>(model: &mut Model, theme: &Theme) -> Table<'a> {
                     .left_aligned(),
                 ),
             ])
+            .style(if song.place.is_some_and(|p| marked.contains(&p.id.0)) {
+                theme.item_marked
+            } else {
+                Style::default()
+            })
             .add_modifier(
                 if song
                     .place
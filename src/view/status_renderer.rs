@@ -1,11 +1,12 @@
 use super::Theme;
-use crate::model::Model;
+use crate::model::{ConnectionState, Model};
 use crate::util::*;
 use mpd::State::*;
 use ratatui::prelude::Constraint::*;
 use ratatui::prelude::*;
 use ratatui::style::Styled;
 use ratatui::widgets::*;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn format_status(state: bool) -> String {
     if state {
@@ -15,6 +16,28 @@ pub fn format_status(state: bool) -> String {
     }
 }
 
+/// The current frame of the spinner shown while the worker is busy. Advances
+/// once per redraw tick.
+pub fn spinner() -> char {
+    const FRAMES: [char; 10] =
+        ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    FRAMES[(millis / 500) as usize % FRAMES.len()]
+}
+
+/// Formats the volume to the width of the "vol" header, or a dash if mpd has
+/// no mixer to control.
+pub fn format_volume(volume: i8) -> String {
+    if volume < 0 {
+        "   -".to_string()
+    } else {
+        format!("{:>3}%", volume)
+    }
+}
+
 pub fn render_status(
     model: &mut Model,
     frame: &mut Frame,
@@ -29,25 +52,47 @@ pub fn render_status(
                     Stop => String::new(),
                 }),
                 Cell::from(
-                    match &model.currentsong {
-                        Some(song) => Line::from(
+                    match (&model.connection, &model.currentsong) {
+                        (ConnectionState::Disconnected { attempt, .. }, _) => {
+                            Line::from(format!(
+                                "disconnected, retrying (attempt {})",
+                                attempt + 1
+                            ))
+                            .set_style(theme.status_stopped)
+                        }
+                        (_, Some(song)) => Line::from(
                             song.title
                                 .clone()
                                 .unwrap_or("<TITLE NOT FOUND>".into()),
-                        ),
-                        None => Line::from("祈"),
+                        )
+                        .set_style(theme.status_title),
+                        (_, None) => {
+                            Line::from("祈").set_style(theme.status_title)
+                        }
                     }
-                    .centered()
-                    .set_style(theme.status_title),
+                    .centered(),
                 ),
-                Cell::from(Line::from("⎡r z s c⎤").right_aligned()),
+                Cell::from(Line::from("⎡vol  r z s c⎤").right_aligned()),
             ]),
             Row::new(vec![
-                Cell::from(match model.status.state {
-                    Play => Text::from("[playing]").style(theme.status_playing),
-                    Pause => Text::from("[paused]").style(theme.status_paused),
-                    Stop => Text::from("[stopped]").style(theme.status_stopped),
-                }),
+                Cell::from(Line::from(vec![
+                    match model.status.state {
+                        Play => {
+                            Span::from("[playing]").style(theme.status_playing)
+                        }
+                        Pause => {
+                            Span::from("[paused]").style(theme.status_paused)
+                        }
+                        Stop => {
+                            Span::from("[stopped]").style(theme.status_stopped)
+                        }
+                    },
+                    Span::from(if model.worker.is_busy() {
+                        format!(" {}", spinner())
+                    } else {
+                        String::new()
+                    }),
+                ])),
                 Cell::from(
                     match &model.currentsong {
                         Some(song) => Line::from(vec![
@@ -71,7 +116,8 @@ pub fn render_status(
                 ),
                 Cell::from(
                     Line::from(format!(
-                        "⎣{} {} {} {}⎦",
+                        "⎣{} {} {} {} {}⎦",
+                        format_volume(model.status.volume),
                         format_status(model.status.repeat),
                         format_status(model.status.random),
                         format_status(model.status.single),
@@ -0,0 +1,66 @@
+use super::layout::track_search_layout::TrackSearchLayout;
+use super::layout::InoriLayout;
+use super::search_renderer::make_search_box;
+use super::status_renderer::render_status;
+use super::Theme;
+use crate::model::*;
+use crate::util::{format_time, song_album};
+use mpd::Song;
+use ratatui::prelude::Constraint::*;
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+fn song_to_row<'a>(song: &Song, theme: &Theme) -> Row<'a> {
+    Row::new(vec![
+        Cell::from(song.title.clone().unwrap_or_default()),
+        Cell::from(
+            Line::from(song.artist.clone().unwrap_or("Unknown Artist".into()))
+                .style(theme.status_artist),
+        ),
+        Cell::from(
+            Line::from(song_album(song).cloned().unwrap_or_default())
+                .style(theme.field_album),
+        ),
+        Cell::from(
+            Line::from(song.duration.map(format_time).unwrap_or_default())
+                .right_aligned(),
+        ),
+    ])
+}
+
+pub fn render(model: &mut Model, frame: &mut Frame, theme: &Theme) {
+    let layout = TrackSearchLayout::new(frame.area(), model);
+    render_status(model, frame, layout.header, theme);
+
+    frame.render_widget(
+        make_search_box(
+            &model.track_search.query,
+            matches!(model.state, State::Searching),
+            theme,
+        ),
+        layout.search,
+    );
+
+    let rows: Vec<Row> = model
+        .track_search
+        .contents
+        .iter()
+        .map(|song| song_to_row(song, theme))
+        .collect();
+    let title = match model.track_search.contents.len() {
+        0 => "Search".to_string(),
+        1 => "Search (1 song)".to_string(),
+        n => format!("Search ({} songs)", n),
+    };
+    let table = Table::new(
+        rows,
+        vec![Percentage(45), Percentage(25), Percentage(30), Min(7)],
+    )
+    .row_highlight_style(theme.item_highlight_active)
+    .block(Block::bordered().title(title));
+    frame.render_stateful_widget(
+        table,
+        layout.listing,
+        &mut model.track_search.state,
+    );
+}
@@ -10,6 +10,7 @@ use ratatui::widgets::*;
 use std::time::Duration;
 
 fn itemref_to_row<'a>(
+    library: &LibraryState,
     artist: &ArtistData,
     item: &TrackSelItem,
     width: u16,
@@ -29,6 +30,9 @@ fn itemref_to_row<'a>(
             } else {
                 album_line.push(Span::from(a.name.clone()))
             }
+            if let Some(year) = a.year() {
+                album_line.push(Span::from(format!(" ({})", year)));
+            }
             album_line.push(Span::from(str::repeat("─", width.into())));
             Row::new(vec![
                 Line::from(album_line),
@@ -62,7 +66,9 @@ fn itemref_to_row<'a>(
             ])
         }
     };
-    if idxs.is_some() {
+    if library.is_marked(&artist.name, &item.item) {
+        row.style(theme.item_marked)
+    } else if idxs.is_some() {
         row.style(Style::new().bg(Color::DarkGray))
     } else {
         row
@@ -70,15 +76,15 @@ fn itemref_to_row<'a>(
 }
 
 fn get_track_data<'a>(
-    artist: Option<&ArtistData>,
+    library: &LibraryState,
     theme: &Theme,
     width: u16,
 ) -> Table<'a> {
-    if let Some(artist) = artist {
+    if let Some(artist) = library.selected_item() {
         let items = artist
             .contents()
             .iter()
-            .map(|i| itemref_to_row(artist, i, width, theme))
+            .map(|i| itemref_to_row(library, artist, i, width, theme))
             .collect::<Vec<Row>>();
         Table::new::<Vec<Row>, Vec<Constraint>>(items, vec![Min(10), Max(9)])
     } else {
@@ -92,7 +98,11 @@ pub fn render_track_list(
     area: Rect,
     theme: &Theme,
 ) {
-    let list = get_track_data(model.library.selected_item(), theme, area.width)
+    let title = match model.library.marked.len() {
+        0 => "Tracks".to_string(),
+        n => format!("Tracks [{} marked]", n),
+    };
+    let list = get_track_data(&model.library, theme, area.width)
         .block(
             match model.library.active {
                 ArtistSelector => Block::bordered(),
@@ -100,7 +110,7 @@ pub fn render_track_list(
                     Block::bordered().border_style(theme.block_active)
                 }
             }
-            .title("Tracks"),
+            .title(title),
         )
         .row_highlight_style(match model.library.active {
             ArtistSelector => theme.item_highlight_inactive,
@@ -0,0 +1,70 @@
+use super::Theme;
+use crate::config::keybind::{
+    format_keybind, message_name, KeybindMap, KeybindTarget,
+};
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::prelude::Constraint::*;
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+/// Lists the keys that can follow a pending key sequence, above the last
+/// line of the screen.
+pub fn render_which_key(
+    pending: &[KeyEvent],
+    continuations: &KeybindMap,
+    frame: &mut Frame,
+    theme: &Theme,
+) {
+    let mut entries: Vec<(String, String)> = continuations
+        .0
+        .iter()
+        .map(|(key, target)| {
+            let action = match target {
+                KeybindTarget::Msg(m) => {
+                    message_name(m).unwrap_or("?").to_string()
+                }
+                KeybindTarget::Map(map) => {
+                    format!("+{} more", map.bindings().len())
+                }
+            };
+            (format_keybind(&[*key]), action)
+        })
+        .collect();
+    entries.sort();
+
+    let area = frame.area();
+    let height = (entries.len() as u16 + 2).min(area.height.saturating_sub(1));
+    let [_, popup, _] =
+        Layout::horizontal([Percentage(20), Percentage(60), Percentage(20)])
+            .areas(area);
+    let popup = Rect::new(
+        popup.x,
+        area.bottom().saturating_sub(height + 1),
+        popup.width,
+        height,
+    );
+    let key_width = entries
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    let rows: Vec<Row> = entries
+        .into_iter()
+        .map(|(key, action)| {
+            Row::new(vec![
+                Cell::from(key).style(theme.field_album),
+                Cell::from(action),
+            ])
+        })
+        .collect();
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Table::new(rows, vec![Length(key_width as u16 + 2), Fill(1)]).block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .border_style(theme.block_active)
+                .title(format!("{} -", format_keybind(pending))),
+        ),
+        popup,
+    );
+}
@@ -0,0 +1,213 @@
+use crate::config::MpdTarget;
+use crate::event_handler::{Event, Result};
+use crate::model::{is_connection_error, Model};
+use crate::update::build_files::path_song;
+use crate::update::build_library::library_query;
+use mpd::client::StreamTypes;
+use mpd::search::Operation;
+use mpd::{Client, Query, Song, Term};
+use std::borrow::Cow;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+const UPDATE_POLL: Duration = Duration::from_millis(250);
+const SEARCH_LIMIT: u32 = 1000;
+// tags a track search term can be scoped to with tag:value
+const SEARCH_TAGS: [&str; 7] = [
+    "artist",
+    "albumartist",
+    "album",
+    "title",
+    "genre",
+    "composer",
+    "date",
+];
+
+const SORT_TAGS: [(&str, &str); 4] = [
+    ("albumartist", "albumartistsort"),
+    ("artist", "artistsort"),
+    ("album", "albumsort"),
+    ("composer", "composersort"),
+];
+
+// slow mpd commands, run on the worker's own connection
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Request {
+    UpdateDB,
+    Library(String),
+    GlobalSearch,
+    TrackSearch(String),
+    Tracks(Vec<String>, String),
+    InsertNext(Vec<Songs>, bool),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Songs {
+    Library {
+        tags: Vec<String>,
+        name: String,
+        values: Vec<String>,
+    },
+    File(String),
+}
+
+pub enum Response {
+    DBUpdated,
+    Library(String, Vec<(String, String)>),
+    GlobalSearch(Vec<Vec<String>>),
+    TrackSearch(String, Vec<Song>),
+    Tracks(Vec<String>, String, Vec<Song>),
+    Inserted(Request, usize),
+    Failed(Request, String),
+}
+
+pub struct Worker {
+    tx: Sender<Request>,
+    pub pending: Vec<Request>,
+}
+
+impl Worker {
+    pub fn new(target: MpdTarget, events: Sender<Event>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || run(target, rx, events));
+        Worker {
+            tx,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn request(&mut self, request: Request) {
+        if !self.pending.contains(&request) {
+            // the worker only stops when the event loop is gone
+            let _ = self.tx.send(request.clone());
+            self.pending.push(request);
+        }
+    }
+
+    pub fn finish(&mut self, request: &Request) {
+        self.pending.retain(|r| r != request);
+    }
+
+    pub fn is_busy(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+fn run(target: MpdTarget, rx: Receiver<Request>, events: Sender<Event>) {
+    let mut conn: Option<Client<StreamTypes>> = None;
+    for request in rx {
+        let mut res = match conn.as_mut() {
+            Some(c) => perform(c, &request),
+            None => Model::make_connection(&target)
+                .and_then(|c| perform(conn.insert(c), &request)),
+        };
+        // mpd drops unused connections, so retry once on a fresh one
+        if res.as_ref().is_err_and(|e| is_connection_error(e.as_ref())) {
+            conn = None;
+            res = Model::make_connection(&target)
+                .and_then(|c| perform(conn.insert(c), &request));
+        }
+        let response = match res {
+            Ok(response) => response,
+            Err(e) => {
+                if is_connection_error(e.as_ref()) {
+                    conn = None;
+                }
+                Response::Failed(request, e.to_string())
+            }
+        };
+        if events.send(Event::Worker(response)).is_err() {
+            return;
+        }
+    }
+}
+
+fn perform(
+    conn: &mut Client<StreamTypes>,
+    request: &Request,
+) -> Result<Response> {
+    match request {
+        Request::UpdateDB => {
+            let id = conn.update()?;
+            // wait until this update job is done
+            while conn.status()?.updating_db.is_some_and(|i| i == id) {
+                thread::sleep(UPDATE_POLL);
+            }
+            Ok(Response::DBUpdated)
+        }
+        Request::Library(tag) => {
+            let entries = match SORT_TAGS.iter().find(|(t, _)| t == tag) {
+                Some((_, sort)) => {
+                    conn.list_group_2((sort.to_string(), tag.clone()))?
+                }
+                None => conn
+                    .list(&Term::Tag(Cow::Borrowed(tag)), &Query::new())?
+                    .into_iter()
+                    .map(|value| (tag.clone(), value))
+                    .collect(),
+            };
+            Ok(Response::Library(tag.clone(), entries))
+        }
+        Request::GlobalSearch => {
+            Ok(Response::GlobalSearch(conn.list_groups(vec![
+                "title",
+                "album",
+                "albumartistsort",
+                "albumartist",
+            ])?))
+        }
+        Request::TrackSearch(text) => {
+            let mut query = Query::new();
+            for word in text.split_whitespace() {
+                match word.split_once(':') {
+                    Some((tag, value))
+                        if !value.is_empty() && SEARCH_TAGS.contains(&tag) =>
+                    {
+                        query.and_with_op(
+                            Term::Tag(Cow::Borrowed(tag)),
+                            Operation::Contains,
+                            value,
+                        )
+                    }
+                    _ => {
+                        query.and_with_op(Term::Any, Operation::Contains, word)
+                    }
+                };
+            }
+            let songs = conn.search(&query, (0, SEARCH_LIMIT))?;
+            Ok(Response::TrackSearch(text.clone(), songs))
+        }
+        Request::Tracks(tags, name) => {
+            let query = library_query(tags, name.clone(), Vec::new());
+            let songs = conn.find(&query, None)?;
+            Ok(Response::Tracks(tags.clone(), name.clone(), songs))
+        }
+        Request::InsertNext(sources, play) => {
+            let mut songs = Vec::new();
+            for source in sources {
+                match source {
+                    Songs::Library { tags, name, values } => {
+                        let query =
+                            library_query(tags, name.clone(), values.clone());
+                        songs.extend(conn.find(&query, None)?);
+                    }
+                    Songs::File(file) => songs.push(path_song(file)),
+                }
+            }
+            // right after the current song, or at the end if none
+            let status = conn.status()?;
+            let start = match status.song {
+                Some(current) => current.pos + 1,
+                None => status.queue_len,
+            };
+            for (i, song) in songs.iter().enumerate() {
+                conn.insert(song, start as usize + i)?;
+            }
+            if *play && !songs.is_empty() {
+                conn.switch(start)?;
+            }
+            Ok(Response::Inserted(request.clone(), songs.len()))
+        }
+    }
+}